@@ -1,15 +1,18 @@
 use crate::{
-    backend::native_gpu_future, BindGroupDescriptor, BindGroupLayoutDescriptor, BindingResource,
-    BufferDescriptor, CommandEncoderDescriptor, ComputePipelineDescriptor, Features, Limits,
-    LoadOp, MapMode, Operations, PipelineLayoutDescriptor, RenderPipelineDescriptor,
-    SamplerDescriptor, ShaderModuleSource, SwapChainStatus, TextureDescriptor,
-    TextureViewDescriptor,
+    backend::{
+        error::{self, ErrorSink, ErrorSinkRaw},
+        native_gpu_future, real,
+    },
+    BindGroupDescriptor, BindGroupLayoutDescriptor, BindingResource, BufferDescriptor,
+    CommandEncoderDescriptor, ComputePipelineDescriptor, CreateError, Features, Limits, LoadOp,
+    MapMode, Operations, PipelineLayoutDescriptor, RenderPipelineDescriptor, SamplerDescriptor,
+    ShaderModuleSource, SwapChainStatus, TextureDescriptor, TextureViewDescriptor,
 };
 
 use arrayvec::ArrayVec;
 use futures::future::{ready, Ready};
 use smallvec::SmallVec;
-use std::{ffi::CString, marker::PhantomData, ops::Range, ptr, slice};
+use std::{ffi::CString, fmt, marker::PhantomData, ops::Range, ptr, slice, sync::Arc};
 use typed_arena::Arena;
 
 macro_rules! gfx_select {
@@ -185,40 +188,6 @@ mod pass_impl {
                 count,
             )
         }
-        fn multi_draw_indirect_count(
-            &mut self,
-            indirect_buffer: &wgc::id::BufferId,
-            indirect_offset: wgt::BufferAddress,
-            count_buffer: &wgc::id::BufferId,
-            count_buffer_offset: wgt::BufferAddress,
-            max_count: u32,
-        ) {
-            wgpu_render_pass_multi_draw_indirect_count(
-                self,
-                *indirect_buffer,
-                indirect_offset,
-                *count_buffer,
-                count_buffer_offset,
-                max_count,
-            )
-        }
-        fn multi_draw_indexed_indirect_count(
-            &mut self,
-            indirect_buffer: &wgc::id::BufferId,
-            indirect_offset: wgt::BufferAddress,
-            count_buffer: &wgc::id::BufferId,
-            count_buffer_offset: wgt::BufferAddress,
-            max_count: u32,
-        ) {
-            wgpu_render_pass_multi_draw_indexed_indirect_count(
-                self,
-                *indirect_buffer,
-                indirect_offset,
-                *count_buffer,
-                count_buffer_offset,
-                max_count,
-            )
-        }
     }
 
     impl crate::RenderPassInner<Context> for wgc::command::RenderPass {
@@ -275,6 +244,40 @@ mod pass_impl {
                 )
             }
         }
+        fn multi_draw_indirect_count(
+            &mut self,
+            indirect_buffer: &wgc::id::BufferId,
+            indirect_offset: wgt::BufferAddress,
+            count_buffer: &wgc::id::BufferId,
+            count_buffer_offset: wgt::BufferAddress,
+            max_count: u32,
+        ) {
+            wgpu_render_pass_multi_draw_indirect_count(
+                self,
+                *indirect_buffer,
+                indirect_offset,
+                *count_buffer,
+                count_buffer_offset,
+                max_count,
+            )
+        }
+        fn multi_draw_indexed_indirect_count(
+            &mut self,
+            indirect_buffer: &wgc::id::BufferId,
+            indirect_offset: wgt::BufferAddress,
+            count_buffer: &wgc::id::BufferId,
+            count_buffer_offset: wgt::BufferAddress,
+            max_count: u32,
+        ) {
+            wgpu_render_pass_multi_draw_indexed_indirect_count(
+                self,
+                *indirect_buffer,
+                indirect_offset,
+                *count_buffer,
+                count_buffer_offset,
+                max_count,
+            )
+        }
     }
 
     impl crate::RenderInner<Context> for wgc::command::RenderBundleEncoder {
@@ -361,60 +364,37 @@ mod pass_impl {
         ) {
             wgpu_render_pass_bundle_indexed_indirect(self, *indirect_buffer, indirect_offset)
         }
-        fn multi_draw_indirect(
-            &mut self,
-            _indirect_buffer: &wgc::id::BufferId,
-            _indirect_offset: wgt::BufferAddress,
-            _count: u32,
-        ) {
-            unimplemented!()
-        }
-        fn multi_draw_indexed_indirect(
-            &mut self,
-            _indirect_buffer: &wgc::id::BufferId,
-            _indirect_offset: wgt::BufferAddress,
-            _count: u32,
-        ) {
-            unimplemented!()
-        }
-        fn multi_draw_indirect_count(
-            &mut self,
-            _indirect_buffer: &wgc::id::BufferId,
-            _indirect_offset: wgt::BufferAddress,
-            _count_buffer: &wgc::id::BufferId,
-            _count_buffer_offset: wgt::BufferAddress,
-            _max_count: u32,
-        ) {
-            unimplemented!()
-        }
-        fn multi_draw_indexed_indirect_count(
-            &mut self,
-            _indirect_buffer: &wgc::id::BufferId,
-            _indirect_offset: wgt::BufferAddress,
-            _count_buffer: &wgc::id::BufferId,
-            _count_buffer_offset: wgt::BufferAddress,
-            _max_count: u32,
-        ) {
-            unimplemented!()
-        }
     }
 }
 
 fn map_buffer_copy_view(view: crate::BufferCopyView) -> wgc::command::BufferCopyView {
     wgc::command::BufferCopyView {
-        buffer: view.buffer.id,
+        buffer: *real(&view.buffer.id),
         layout: view.layout,
     }
 }
 
 fn map_texture_copy_view(view: crate::TextureCopyView) -> wgc::command::TextureCopyView {
     wgc::command::TextureCopyView {
-        texture: view.texture.id,
+        texture: *real(&view.texture.id),
         mip_level: view.mip_level,
         origin: view.origin,
     }
 }
 
+/// Converts an error returned by wgpu-core.
+///
+/// The errors wgpu-core returns at this revision all come from validation: it panics on
+/// allocation failures instead of returning them.
+fn map_create_error(cause: impl fmt::Debug) -> CreateError {
+    CreateError::Validation(format!("{:?}", cause))
+}
+
+/// Reports an error from an operation that has no way to return it.
+fn handle_error(sink: &ErrorSink, cause: impl fmt::Debug) {
+    error::handle_error(sink, map_create_error(cause).into());
+}
+
 fn map_pass_channel<V: Copy + Default>(ops: Option<&Operations<V>>) -> wgt::PassChannel<V> {
     match ops {
         Some(&Operations {
@@ -454,7 +434,7 @@ fn map_pass_channel<V: Copy + Default>(ops: Option<&Operations<V>>) -> wgt::Pass
 
 impl crate::Context for Context {
     type AdapterId = wgc::id::AdapterId;
-    type DeviceId = wgc::id::DeviceId;
+    type DeviceId = Device;
     type QueueId = wgc::id::QueueId;
     type ShaderModuleId = wgc::id::ShaderModuleId;
     type BindGroupLayoutId = wgc::id::BindGroupLayoutId;
@@ -466,7 +446,7 @@ impl crate::Context for Context {
     type PipelineLayoutId = wgc::id::PipelineLayoutId;
     type RenderPipelineId = wgc::id::RenderPipelineId;
     type ComputePipelineId = wgc::id::ComputePipelineId;
-    type CommandEncoderId = wgc::id::CommandEncoderId;
+    type CommandEncoderId = CommandEncoder;
     type ComputePassId = wgc::command::ComputePass;
     type RenderPassId = wgc::command::RenderPass;
     type CommandBufferId = wgc::id::CommandBufferId;
@@ -481,6 +461,7 @@ impl crate::Context for Context {
     type RequestDeviceFuture =
         Ready<Result<(Self::DeviceId, Self::QueueId), crate::RequestDeviceError>>;
     type MapAsyncFuture = native_gpu_future::GpuFuture<Result<(), crate::BufferAsyncError>>;
+    type PopErrorScopeFuture = Ready<Option<crate::Error>>;
 
     fn init(backends: wgt::BackendBit) -> Self {
         wgc::hub::Global::new("wgpu", wgc::hub::IdentityManagerFactory, backends)
@@ -500,7 +481,7 @@ impl crate::Context for Context {
         let id = self.pick_adapter(
             &wgc::instance::RequestAdapterOptions {
                 power_preference: options.power_preference,
-                compatible_surface: options.compatible_surface.map(|surface| surface.id),
+                compatible_surface: options.compatible_surface.map(|surface| *real(&surface.id)),
             },
             wgc::instance::AdapterInputs::Mask(wgt::BackendBit::all(), |_| PhantomData),
         );
@@ -514,7 +495,11 @@ impl crate::Context for Context {
         trace_dir: Option<&std::path::Path>,
     ) -> Self::RequestDeviceFuture {
         let device_id = gfx_select!(*adapter => self.adapter_request_device(*adapter, desc, trace_dir, PhantomData)).unwrap();
-        ready(Ok((device_id, device_id)))
+        let device = Device {
+            id: device_id,
+            error_sink: ErrorSinkRaw::new_sink(),
+        };
+        ready(Ok((device, device_id)))
     }
 
     fn adapter_features(&self, adapter: &Self::AdapterId) -> Features {
@@ -526,11 +511,11 @@ impl crate::Context for Context {
     }
 
     fn device_features(&self, device: &Self::DeviceId) -> Features {
-        gfx_select!(*device => self.device_features(*device))
+        gfx_select!(device.id => self.device_features(device.id))
     }
 
     fn device_limits(&self, device: &Self::DeviceId) -> Limits {
-        gfx_select!(*device => self.device_limits(*device))
+        gfx_select!(device.id => self.device_limits(device.id))
     }
 
     fn device_create_swap_chain(
@@ -539,39 +524,39 @@ impl crate::Context for Context {
         surface: &Self::SurfaceId,
         desc: &wgt::SwapChainDescriptor,
     ) -> Self::SwapChainId {
-        gfx_select!(*device => self.device_create_swap_chain(*device, *surface, desc))
+        gfx_select!(device.id => self.device_create_swap_chain(device.id, *surface, desc))
     }
 
     fn device_create_shader_module(
         &self,
         device: &Self::DeviceId,
         source: ShaderModuleSource,
-    ) -> Self::ShaderModuleId {
+    ) -> Result<Self::ShaderModuleId, CreateError> {
         let desc = match source {
             ShaderModuleSource::SpirV(spv) => wgc::pipeline::ShaderModuleSource::SpirV(spv),
             ShaderModuleSource::Wgsl(code) => wgc::pipeline::ShaderModuleSource::Wgsl(code),
         };
-        gfx_select!(*device => self.device_create_shader_module(*device, desc, PhantomData))
+        Ok(gfx_select!(device.id => self.device_create_shader_module(device.id, desc, PhantomData)))
     }
 
     fn device_create_bind_group_layout(
         &self,
         device: &Self::DeviceId,
         desc: &BindGroupLayoutDescriptor,
-    ) -> Self::BindGroupLayoutId {
-        gfx_select!(*device => self.device_create_bind_group_layout(
-            *device,
+    ) -> Result<Self::BindGroupLayoutId, CreateError> {
+        gfx_select!(device.id => self.device_create_bind_group_layout(
+            device.id,
             desc,
             PhantomData
         ))
-        .unwrap()
+        .map_err(map_create_error)
     }
 
     fn device_create_bind_group(
         &self,
         device: &Self::DeviceId,
         desc: &BindGroupDescriptor,
-    ) -> Self::BindGroupId {
+    ) -> Result<Self::BindGroupId, CreateError> {
         wgc::span!(_guard, TRACE, "Device::create_bind_group wrapper");
         use wgc::binding_model as bm;
 
@@ -584,44 +569,42 @@ impl crate::Context for Context {
                 resource: match entry.resource {
                     BindingResource::Buffer(ref buffer_slice) => {
                         bm::BindingResource::Buffer(bm::BufferBinding {
-                            buffer_id: buffer_slice.buffer.id,
+                            buffer_id: *real(&buffer_slice.buffer.id),
                             offset: buffer_slice.offset,
                             size: buffer_slice.size,
                         })
                     }
                     BindingResource::Sampler(ref sampler) => {
-                        bm::BindingResource::Sampler(sampler.id)
+                        bm::BindingResource::Sampler(*real(&sampler.id))
                     }
                     BindingResource::TextureView(ref texture_view) => {
-                        bm::BindingResource::TextureView(texture_view.id)
+                        bm::BindingResource::TextureView(*real(&texture_view.id))
                     }
                     BindingResource::TextureViewArray(texture_view_array) => {
-                        bm::BindingResource::TextureViewArray(
-                            texture_view_arena
-                                .alloc_extend(texture_view_array.iter().map(|view| view.id)),
-                        )
+                        let ids = texture_view_array.iter().map(|view| *real(&view.id));
+                        bm::BindingResource::TextureViewArray(texture_view_arena.alloc_extend(ids))
                     }
                 },
             })
             .collect::<Vec<_>>();
 
-        gfx_select!(*device => self.device_create_bind_group(
-            *device,
+        gfx_select!(device.id => self.device_create_bind_group(
+            device.id,
             &bm::BindGroupDescriptor {
                 label: desc.label,
-                layout: desc.layout.id,
+                layout: *real(&desc.layout.id),
                 entries: &entries,
             },
             PhantomData
         ))
-        .unwrap()
+        .map_err(map_create_error)
     }
 
     fn device_create_pipeline_layout(
         &self,
         device: &Self::DeviceId,
         desc: &PipelineLayoutDescriptor,
-    ) -> Self::PipelineLayoutId {
+    ) -> Result<Self::PipelineLayoutId, CreateError> {
         wgc::span!(_guard, TRACE, "Device::create_pipeline_layout wrapper");
 
         // Limit is always less or equal to wgc::MAX_BIND_GROUPS, so this is always right
@@ -636,44 +619,44 @@ impl crate::Context for Context {
         let temp_layouts = desc
             .bind_group_layouts
             .iter()
-            .map(|bgl| bgl.id)
+            .map(|bgl| *real(&bgl.id))
             .collect::<ArrayVec<[_; wgc::MAX_BIND_GROUPS]>>();
 
-        gfx_select!(*device => self.device_create_pipeline_layout(
-            *device,
+        gfx_select!(device.id => self.device_create_pipeline_layout(
+            device.id,
             &wgt::PipelineLayoutDescriptor {
                 bind_group_layouts: &temp_layouts,
                 push_constant_ranges: &desc.push_constant_ranges,
             },
             PhantomData
         ))
-        .unwrap()
+        .map_err(map_create_error)
     }
 
     fn device_create_render_pipeline(
         &self,
         device: &Self::DeviceId,
         desc: &RenderPipelineDescriptor,
-    ) -> Self::RenderPipelineId {
+    ) -> Result<Self::RenderPipelineId, CreateError> {
         wgc::span!(_guard, TRACE, "Device::create_render_pipeline wrapper");
         use wgc::pipeline as pipe;
 
         let vertex_stage = pipe::ProgrammableStageDescriptor {
-            module: desc.vertex_stage.module.id,
+            module: *real(&desc.vertex_stage.module.id),
             entry_point: desc.vertex_stage.entry_point,
         };
         let fragment_stage =
             desc.fragment_stage
                 .as_ref()
                 .map(|fs| pipe::ProgrammableStageDescriptor {
-                    module: fs.module.id,
+                    module: *real(&fs.module.id),
                     entry_point: fs.entry_point,
                 });
 
-        gfx_select!(*device => self.device_create_render_pipeline(
-            *device,
+        gfx_select!(device.id => self.device_create_render_pipeline(
+            device.id,
             &pipe::RenderPipelineDescriptor {
-                layout: desc.layout.id,
+                layout: *real(&desc.layout.id),
                 vertex_stage,
                 fragment_stage,
                 rasterization_state: desc.rasterization_state.clone(),
@@ -687,67 +670,67 @@ impl crate::Context for Context {
             },
             PhantomData
         ))
-        .unwrap()
+        .map_err(map_create_error)
     }
 
     fn device_create_compute_pipeline(
         &self,
         device: &Self::DeviceId,
         desc: &ComputePipelineDescriptor,
-    ) -> Self::ComputePipelineId {
+    ) -> Result<Self::ComputePipelineId, CreateError> {
         use wgc::pipeline as pipe;
 
-        gfx_select!(*device => self.device_create_compute_pipeline(
-            *device,
+        gfx_select!(device.id => self.device_create_compute_pipeline(
+            device.id,
             &pipe::ComputePipelineDescriptor {
-                layout: desc.layout.id,
+                layout: *real(&desc.layout.id),
                 compute_stage: pipe::ProgrammableStageDescriptor {
-                    module: desc.compute_stage.module.id,
+                    module: *real(&desc.compute_stage.module.id),
                     entry_point: desc.compute_stage.entry_point,
                 },
             },
             PhantomData
         ))
-        .unwrap()
+        .map_err(map_create_error)
     }
 
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
         desc: &BufferDescriptor,
-    ) -> Self::BufferId {
+    ) -> Result<Self::BufferId, CreateError> {
         let owned_label = OwnedLabel::new(desc.label.as_deref());
-        gfx_select!(*device => self.device_create_buffer(
-            *device,
+        Ok(gfx_select!(device.id => self.device_create_buffer(
+            device.id,
             &desc.map_label(|_| owned_label.as_ptr()),
             PhantomData
-        ))
+        )))
     }
 
     fn device_create_texture(
         &self,
         device: &Self::DeviceId,
         desc: &TextureDescriptor,
-    ) -> Self::TextureId {
+    ) -> Result<Self::TextureId, CreateError> {
         let owned_label = OwnedLabel::new(desc.label.as_deref());
-        gfx_select!(*device => self.device_create_texture(
-            *device,
+        Ok(gfx_select!(device.id => self.device_create_texture(
+            device.id,
             &desc.map_label(|_| owned_label.as_ptr()),
             PhantomData
-        ))
+        )))
     }
 
     fn device_create_sampler(
         &self,
         device: &Self::DeviceId,
         desc: &SamplerDescriptor,
-    ) -> Self::SamplerId {
+    ) -> Result<Self::SamplerId, CreateError> {
         let owned_label = OwnedLabel::new(desc.label.as_deref());
-        gfx_select!(*device => self.device_create_sampler(
-            *device,
+        Ok(gfx_select!(device.id => self.device_create_sampler(
+            device.id,
             &desc.map_label(|_| owned_label.as_ptr()),
             PhantomData
-        ))
+        )))
     }
 
     fn device_create_command_encoder(
@@ -756,13 +739,16 @@ impl crate::Context for Context {
         desc: &CommandEncoderDescriptor,
     ) -> Self::CommandEncoderId {
         let owned_label = OwnedLabel::new(desc.label.as_deref());
-        gfx_select!(*device => self.device_create_command_encoder(
-            *device,
-            &wgt::CommandEncoderDescriptor {
-                label: owned_label.as_ptr(),
-            },
-            PhantomData
-        ))
+        CommandEncoder {
+            id: gfx_select!(device.id => self.device_create_command_encoder(
+                device.id,
+                &wgt::CommandEncoderDescriptor {
+                    label: owned_label.as_ptr(),
+                },
+                PhantomData
+            )),
+            error_sink: Arc::clone(&device.error_sink),
+        }
     }
 
     fn device_create_render_bundle_encoder(
@@ -770,21 +756,21 @@ impl crate::Context for Context {
         device: &Self::DeviceId,
         desc: &wgt::RenderBundleEncoderDescriptor,
     ) -> Self::RenderBundleEncoderId {
-        wgc::command::RenderBundleEncoder::new(desc, *device, None)
+        wgc::command::RenderBundleEncoder::new(desc, device.id, None)
     }
 
     fn device_drop(&self, device: &Self::DeviceId) {
         #[cfg(not(target_arch = "wasm32"))]
-        gfx_select!(*device => self.device_poll(*device, true));
+        gfx_select!(device.id => self.device_poll(device.id, true));
         //TODO: make this work in general
         #[cfg(not(target_arch = "wasm32"))]
         #[cfg(feature = "metal-auto-capture")]
-        gfx_select!(*device => self.device_destroy(*device));
+        gfx_select!(device.id => self.device_destroy(device.id));
     }
 
     fn device_poll(&self, device: &Self::DeviceId, maintain: crate::Maintain) {
-        gfx_select!(*device => self.device_poll(
-            *device,
+        gfx_select!(device.id => self.device_poll(
+            device.id,
             match maintain {
                 crate::Maintain::Poll => false,
                 crate::Maintain::Wait => true,
@@ -792,6 +778,33 @@ impl crate::Context for Context {
         ));
     }
 
+    fn device_push_error_scope(&self, device: &Self::DeviceId, filter: crate::ErrorFilter) {
+        device.error_sink.lock().push_scope(filter);
+    }
+
+    fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture {
+        ready(device.error_sink.lock().pop_scope())
+    }
+
+    fn device_on_uncaptured_error(
+        &self,
+        device: &Self::DeviceId,
+        handler: Box<dyn Fn(crate::Error) + Send + Sync>,
+    ) {
+        device
+            .error_sink
+            .lock()
+            .set_uncaptured_handler(handler.into());
+    }
+
+    fn device_capture_error(&self, device: &Self::DeviceId, error: crate::Error) {
+        let _ = device.error_sink.lock().capture(error);
+    }
+
+    fn device_report_error(&self, device: &Self::DeviceId, error: crate::Error) {
+        error::handle_error(&device.error_sink, error);
+    }
+
     fn buffer_map_async(
         &self,
         buffer: &Self::BufferId,
@@ -939,15 +952,16 @@ impl crate::Context for Context {
         destination_offset: wgt::BufferAddress,
         copy_size: wgt::BufferAddress,
     ) {
-        gfx_select!(*encoder => self.command_encoder_copy_buffer_to_buffer(
-            *encoder,
+        if let Err(cause) = gfx_select!(encoder.id => self.command_encoder_copy_buffer_to_buffer(
+            encoder.id,
             *source,
             source_offset,
             *destination,
             destination_offset,
             copy_size
-        ))
-        .unwrap()
+        )) {
+            handle_error(&encoder.error_sink, cause);
+        }
     }
 
     fn command_encoder_copy_buffer_to_texture(
@@ -957,13 +971,14 @@ impl crate::Context for Context {
         destination: crate::TextureCopyView,
         copy_size: wgt::Extent3d,
     ) {
-        gfx_select!(*encoder => self.command_encoder_copy_buffer_to_texture(
-            *encoder,
+        if let Err(cause) = gfx_select!(encoder.id => self.command_encoder_copy_buffer_to_texture(
+            encoder.id,
             &map_buffer_copy_view(source),
             &map_texture_copy_view(destination),
             &copy_size
-        ))
-        .unwrap()
+        )) {
+            handle_error(&encoder.error_sink, cause);
+        }
     }
 
     fn command_encoder_copy_texture_to_buffer(
@@ -973,13 +988,14 @@ impl crate::Context for Context {
         destination: crate::BufferCopyView,
         copy_size: wgt::Extent3d,
     ) {
-        gfx_select!(*encoder => self.command_encoder_copy_texture_to_buffer(
-            *encoder,
+        if let Err(cause) = gfx_select!(encoder.id => self.command_encoder_copy_texture_to_buffer(
+            encoder.id,
             &map_texture_copy_view(source),
             &map_buffer_copy_view(destination),
             &copy_size
-        ))
-        .unwrap()
+        )) {
+            handle_error(&encoder.error_sink, cause);
+        }
     }
 
     fn command_encoder_copy_texture_to_texture(
@@ -989,20 +1005,21 @@ impl crate::Context for Context {
         destination: crate::TextureCopyView,
         copy_size: wgt::Extent3d,
     ) {
-        gfx_select!(*encoder => self.command_encoder_copy_texture_to_texture(
-            *encoder,
+        if let Err(cause) = gfx_select!(encoder.id => self.command_encoder_copy_texture_to_texture(
+            encoder.id,
             &map_texture_copy_view(source),
             &map_texture_copy_view(destination),
             &copy_size
-        ))
-        .unwrap()
+        )) {
+            handle_error(&encoder.error_sink, cause);
+        }
     }
 
     fn command_encoder_begin_compute_pass(
         &self,
         encoder: &Self::CommandEncoderId,
     ) -> Self::ComputePassId {
-        wgc::command::ComputePass::new(*encoder)
+        wgc::command::ComputePass::new(encoder.id)
     }
 
     fn command_encoder_end_compute_pass(
@@ -1010,7 +1027,7 @@ impl crate::Context for Context {
         encoder: &Self::CommandEncoderId,
         pass: &mut Self::ComputePassId,
     ) {
-        gfx_select!(*encoder => self.command_encoder_run_compute_pass(*encoder, pass));
+        gfx_select!(encoder.id => self.command_encoder_run_compute_pass(encoder.id, pass));
     }
 
     fn command_encoder_begin_render_pass<'a>(
@@ -1023,22 +1040,22 @@ impl crate::Context for Context {
             .color_attachments
             .iter()
             .map(|ca| wgc::command::ColorAttachmentDescriptor {
-                attachment: ca.attachment.id,
-                resolve_target: ca.resolve_target.map(|rt| rt.id),
+                attachment: *real(&ca.attachment.id),
+                resolve_target: ca.resolve_target.map(|rt| *real(&rt.id)),
                 channel: map_pass_channel(Some(&ca.ops)),
             })
             .collect::<ArrayVec<[_; wgc::device::MAX_COLOR_TARGETS]>>();
 
         let depth_stencil = desc.depth_stencil_attachment.as_ref().map(|dsa| {
             wgc::command::DepthStencilAttachmentDescriptor {
-                attachment: dsa.attachment.id,
+                attachment: *real(&dsa.attachment.id),
                 depth: map_pass_channel(dsa.depth_ops.as_ref()),
                 stencil: map_pass_channel(dsa.stencil_ops.as_ref()),
             }
         });
 
         wgc::command::RenderPass::new(
-            *encoder,
+            encoder.id,
             wgc::command::RenderPassDescriptor {
                 color_attachments: &colors,
                 depth_stencil_attachment: depth_stencil.as_ref(),
@@ -1051,12 +1068,12 @@ impl crate::Context for Context {
         encoder: &Self::CommandEncoderId,
         pass: &mut Self::RenderPassId,
     ) {
-        gfx_select!(*encoder => self.command_encoder_run_render_pass(*encoder, pass));
+        gfx_select!(encoder.id => self.command_encoder_run_render_pass(encoder.id, pass));
     }
 
     fn command_encoder_finish(&self, encoder: &Self::CommandEncoderId) -> Self::CommandBufferId {
         let desc = wgt::CommandBufferDescriptor::default();
-        gfx_select!(*encoder => self.command_encoder_finish(*encoder, &desc))
+        gfx_select!(encoder.id => self.command_encoder_finish(encoder.id, &desc))
     }
 
     fn render_bundle_encoder_finish(
@@ -1110,6 +1127,18 @@ impl crate::Context for Context {
     }
 }
 
+#[derive(Debug)]
+pub(crate) struct Device {
+    id: wgc::id::DeviceId,
+    error_sink: ErrorSink,
+}
+
+#[derive(Debug)]
+pub(crate) struct CommandEncoder {
+    id: wgc::id::CommandEncoderId,
+    error_sink: ErrorSink,
+}
+
 #[derive(Debug)]
 pub(crate) struct SwapChainOutputDetail {
     swap_chain_id: wgc::id::SwapChainId,
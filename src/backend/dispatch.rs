@@ -0,0 +1,902 @@
+//! Context that dispatches to either the real backend or the mock one.
+//!
+//! Compiled with the `mock` feature, so that an [`Instance`](crate::Instance) can be created
+//! for the mock backend with `Instance::new_mock` while `Instance::new` keeps using the real one.
+//! Every identifier remembers which backend it belongs to.
+
+#[cfg(not(target_arch = "wasm32"))]
+use super::direct::Context as RealContext;
+use super::mock::Context as MockContext;
+#[cfg(target_arch = "wasm32")]
+use super::web::Context as RealContext;
+use crate::{
+    BindGroupDescriptor, BindGroupLayoutDescriptor, BufferAddress, BufferCopyView,
+    BufferDescriptor, BufferSize, Color, CommandEncoderDescriptor, ComputePassInner,
+    ComputePipelineDescriptor, CreateError, DeviceDescriptor, DynamicOffset, Error, ErrorFilter,
+    Extent3d, Features, Limits, Maintain, MapMode, PipelineLayoutDescriptor,
+    RenderBundleDescriptor, RenderBundleEncoderDescriptor, RenderInner, RenderPassDescriptor,
+    RenderPassInner, RenderPipelineDescriptor, RequestAdapterOptions, RequestDeviceError,
+    SamplerDescriptor, ShaderModuleSource, SwapChainDescriptor, SwapChainStatus, TextureCopyView,
+    TextureDataLayout, TextureDescriptor, TextureViewDescriptor,
+};
+
+use futures::{
+    future::{BoxFuture, Either},
+    FutureExt,
+};
+use std::ops::Range;
+
+/// Object of the real backend or of the mock one.
+#[derive(Debug)]
+pub(crate) enum Backend<R, M> {
+    Real(R),
+    Mock(M),
+}
+
+impl<R, M> Backend<R, M> {
+    pub(crate) fn real(&self) -> &R {
+        match self {
+            Backend::Real(real) => real,
+            Backend::Mock(_) => panic!("Object of the mock backend used with the real one"),
+        }
+    }
+
+    fn real_mut(&mut self) -> &mut R {
+        match self {
+            Backend::Real(real) => real,
+            Backend::Mock(_) => panic!("Object of the mock backend used with the real one"),
+        }
+    }
+
+    fn into_real(self) -> R {
+        match self {
+            Backend::Real(real) => real,
+            Backend::Mock(_) => panic!("Object of the mock backend used with the real one"),
+        }
+    }
+
+    pub(crate) fn mock(&self) -> &M {
+        match self {
+            Backend::Real(_) => panic!("Object of the real backend used with the mock one"),
+            Backend::Mock(mock) => mock,
+        }
+    }
+
+    fn mock_mut(&mut self) -> &mut M {
+        match self {
+            Backend::Real(_) => panic!("Object of the real backend used with the mock one"),
+            Backend::Mock(mock) => mock,
+        }
+    }
+
+    fn into_mock(self) -> M {
+        match self {
+            Backend::Real(_) => panic!("Object of the real backend used with the mock one"),
+            Backend::Mock(mock) => mock,
+        }
+    }
+}
+
+/// Returns the object of the real backend inside `object`.
+///
+/// # Panics
+///
+/// - `object` belongs to the mock backend.
+pub(crate) fn real<R, M>(object: &Backend<R, M>) -> &R {
+    object.real()
+}
+
+/// Wraps an object of the real backend.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn from_real<R, M>(object: R) -> Backend<R, M> {
+    Backend::Real(object)
+}
+
+pub(crate) type Context = Backend<RealContext, MockContext>;
+
+impl Context {
+    pub(crate) fn new_mock() -> Self {
+        Backend::Mock(crate::Context::init(wgt::BackendBit::empty()))
+    }
+}
+
+/// Identifier type of both backends.
+macro_rules! id {
+    ($name:ident) => {
+        Backend<<RealContext as crate::Context>::$name, <MockContext as crate::Context>::$name>
+    };
+}
+
+/// Evaluates `$body` with `$ctx` bound to the context of the backend `$context` belongs to,
+/// and each of the `$id`s unwrapped for that backend. `$wrap` wraps results back.
+macro_rules! dispatch {
+    ($context:expr, ($($id:ident),*) => |$ctx:ident $(, $wrap:ident)?| $body:expr) => {
+        match $context {
+            Backend::Real($ctx) => {
+                $(let $id = $id.real();)*
+                $(use Backend::Real as $wrap;)?
+                $body
+            }
+            Backend::Mock($ctx) => {
+                $(let $id = $id.mock();)*
+                $(use Backend::Mock as $wrap;)?
+                $body
+            }
+        }
+    };
+}
+
+impl<R, M> ComputePassInner<Context> for Backend<R, M>
+where
+    R: ComputePassInner<RealContext>,
+    M: ComputePassInner<MockContext>,
+{
+    fn set_pipeline(&mut self, pipeline: &id!(ComputePipelineId)) {
+        dispatch!(self, (pipeline) => |pass| pass.set_pipeline(pipeline))
+    }
+    fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group: &id!(BindGroupId),
+        offsets: &[DynamicOffset],
+    ) {
+        dispatch!(self, (bind_group) => |pass| pass.set_bind_group(index, bind_group, offsets))
+    }
+    fn set_push_constants(&mut self, offset: u32, data: &[u32]) {
+        dispatch!(self, () => |pass| pass.set_push_constants(offset, data))
+    }
+    fn dispatch(&mut self, x: u32, y: u32, z: u32) {
+        dispatch!(self, () => |pass| pass.dispatch(x, y, z))
+    }
+    fn dispatch_indirect(
+        &mut self,
+        indirect_buffer: &id!(BufferId),
+        indirect_offset: BufferAddress,
+    ) {
+        dispatch!(self, (indirect_buffer) => |pass| {
+            pass.dispatch_indirect(indirect_buffer, indirect_offset)
+        })
+    }
+}
+
+impl<R, M> RenderInner<Context> for Backend<R, M>
+where
+    R: RenderInner<RealContext>,
+    M: RenderInner<MockContext>,
+{
+    fn set_pipeline(&mut self, pipeline: &id!(RenderPipelineId)) {
+        dispatch!(self, (pipeline) => |pass| pass.set_pipeline(pipeline))
+    }
+    fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group: &id!(BindGroupId),
+        offsets: &[DynamicOffset],
+    ) {
+        dispatch!(self, (bind_group) => |pass| pass.set_bind_group(index, bind_group, offsets))
+    }
+    fn set_index_buffer(
+        &mut self,
+        buffer: &id!(BufferId),
+        offset: BufferAddress,
+        size: Option<BufferSize>,
+    ) {
+        dispatch!(self, (buffer) => |pass| pass.set_index_buffer(buffer, offset, size))
+    }
+    fn set_vertex_buffer(
+        &mut self,
+        slot: u32,
+        buffer: &id!(BufferId),
+        offset: BufferAddress,
+        size: Option<BufferSize>,
+    ) {
+        dispatch!(self, (buffer) => |pass| pass.set_vertex_buffer(slot, buffer, offset, size))
+    }
+    fn set_push_constants(&mut self, stages: wgt::ShaderStage, offset: u32, data: &[u32]) {
+        dispatch!(self, () => |pass| pass.set_push_constants(stages, offset, data))
+    }
+    fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        dispatch!(self, () => |pass| pass.draw(vertices, instances))
+    }
+    fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
+        dispatch!(self, () => |pass| pass.draw_indexed(indices, base_vertex, instances))
+    }
+    fn draw_indirect(&mut self, indirect_buffer: &id!(BufferId), indirect_offset: BufferAddress) {
+        dispatch!(self, (indirect_buffer) => |pass| {
+            pass.draw_indirect(indirect_buffer, indirect_offset)
+        })
+    }
+    fn draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: &id!(BufferId),
+        indirect_offset: BufferAddress,
+    ) {
+        dispatch!(self, (indirect_buffer) => |pass| {
+            pass.draw_indexed_indirect(indirect_buffer, indirect_offset)
+        })
+    }
+    fn multi_draw_indirect(
+        &mut self,
+        indirect_buffer: &id!(BufferId),
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        dispatch!(self, (indirect_buffer) => |pass| {
+            pass.multi_draw_indirect(indirect_buffer, indirect_offset, count)
+        })
+    }
+    fn multi_draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: &id!(BufferId),
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        dispatch!(self, (indirect_buffer) => |pass| {
+            pass.multi_draw_indexed_indirect(indirect_buffer, indirect_offset, count)
+        })
+    }
+}
+
+impl<R, M> RenderPassInner<Context> for Backend<R, M>
+where
+    R: RenderPassInner<RealContext>,
+    M: RenderPassInner<MockContext>,
+{
+    fn set_blend_color(&mut self, color: Color) {
+        dispatch!(self, () => |pass| pass.set_blend_color(color))
+    }
+    fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        dispatch!(self, () => |pass| pass.set_scissor_rect(x, y, width, height))
+    }
+    fn set_viewport(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        min_depth: f32,
+        max_depth: f32,
+    ) {
+        dispatch!(self, () => |pass| pass.set_viewport(x, y, width, height, min_depth, max_depth))
+    }
+    fn set_stencil_reference(&mut self, reference: u32) {
+        dispatch!(self, () => |pass| pass.set_stencil_reference(reference))
+    }
+    fn insert_debug_marker(&mut self, label: &str) {
+        dispatch!(self, () => |pass| pass.insert_debug_marker(label))
+    }
+    fn push_debug_group(&mut self, group_label: &str) {
+        dispatch!(self, () => |pass| pass.push_debug_group(group_label))
+    }
+    fn pop_debug_group(&mut self) {
+        dispatch!(self, () => |pass| pass.pop_debug_group())
+    }
+    fn execute_bundles<'a, I: Iterator<Item = &'a id!(RenderBundleId)>>(
+        &mut self,
+        render_bundles: I,
+    ) {
+        match self {
+            Backend::Real(pass) => pass.execute_bundles(render_bundles.map(Backend::real)),
+            Backend::Mock(pass) => pass.execute_bundles(render_bundles.map(Backend::mock)),
+        }
+    }
+    fn multi_draw_indirect_count(
+        &mut self,
+        indirect_buffer: &id!(BufferId),
+        indirect_offset: BufferAddress,
+        count_buffer: &id!(BufferId),
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        dispatch!(self, (indirect_buffer, count_buffer) => |pass| {
+            pass.multi_draw_indirect_count(
+                indirect_buffer,
+                indirect_offset,
+                count_buffer,
+                count_buffer_offset,
+                max_count,
+            )
+        })
+    }
+    fn multi_draw_indexed_indirect_count(
+        &mut self,
+        indirect_buffer: &id!(BufferId),
+        indirect_offset: BufferAddress,
+        count_buffer: &id!(BufferId),
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        dispatch!(self, (indirect_buffer, count_buffer) => |pass| {
+            pass.multi_draw_indexed_indirect_count(
+                indirect_buffer,
+                indirect_offset,
+                count_buffer,
+                count_buffer_offset,
+                max_count,
+            )
+        })
+    }
+}
+
+impl crate::Context for Context {
+    type AdapterId = id!(AdapterId);
+    type DeviceId = id!(DeviceId);
+    type QueueId = id!(QueueId);
+    type ShaderModuleId = id!(ShaderModuleId);
+    type BindGroupLayoutId = id!(BindGroupLayoutId);
+    type BindGroupId = id!(BindGroupId);
+    type TextureViewId = id!(TextureViewId);
+    type SamplerId = id!(SamplerId);
+    type BufferId = id!(BufferId);
+    type TextureId = id!(TextureId);
+    type PipelineLayoutId = id!(PipelineLayoutId);
+    type RenderPipelineId = id!(RenderPipelineId);
+    type ComputePipelineId = id!(ComputePipelineId);
+    type CommandEncoderId = id!(CommandEncoderId);
+    type ComputePassId = id!(ComputePassId);
+    type RenderPassId = id!(RenderPassId);
+    type CommandBufferId = id!(CommandBufferId);
+    type RenderBundleEncoderId = id!(RenderBundleEncoderId);
+    type RenderBundleId = id!(RenderBundleId);
+    type SurfaceId = id!(SurfaceId);
+    type SwapChainId = id!(SwapChainId);
+
+    type SwapChainOutputDetail = id!(SwapChainOutputDetail);
+
+    type RequestAdapterFuture = BoxFuture<'static, Option<Self::AdapterId>>;
+    type RequestDeviceFuture =
+        BoxFuture<'static, Result<(Self::DeviceId, Self::QueueId), RequestDeviceError>>;
+    type MapAsyncFuture = Either<
+        <RealContext as crate::Context>::MapAsyncFuture,
+        <MockContext as crate::Context>::MapAsyncFuture,
+    >;
+    type PopErrorScopeFuture = Either<
+        <RealContext as crate::Context>::PopErrorScopeFuture,
+        <MockContext as crate::Context>::PopErrorScopeFuture,
+    >;
+
+    fn init(backends: wgt::BackendBit) -> Self {
+        Backend::Real(crate::Context::init(backends))
+    }
+
+    fn instance_create_surface(
+        &self,
+        handle: &impl raw_window_handle::HasRawWindowHandle,
+    ) -> Self::SurfaceId {
+        dispatch!(self, () => |ctx, Wrap| {
+            Wrap(crate::Context::instance_create_surface(ctx, handle))
+        })
+    }
+
+    fn instance_request_adapter(
+        &self,
+        options: &RequestAdapterOptions<'_>,
+    ) -> Self::RequestAdapterFuture {
+        dispatch!(self, () => |ctx, Wrap| {
+            crate::Context::instance_request_adapter(ctx, options)
+                .map(|adapter| adapter.map(Wrap))
+                .boxed()
+        })
+    }
+
+    fn adapter_request_device(
+        &self,
+        adapter: &Self::AdapterId,
+        desc: &DeviceDescriptor,
+        trace_dir: Option<&std::path::Path>,
+    ) -> Self::RequestDeviceFuture {
+        dispatch!(self, (adapter) => |ctx, Wrap| {
+            crate::Context::adapter_request_device(ctx, adapter, desc, trace_dir)
+                .map(|result| result.map(|(device, queue)| (Wrap(device), Wrap(queue))))
+                .boxed()
+        })
+    }
+
+    fn adapter_features(&self, adapter: &Self::AdapterId) -> Features {
+        dispatch!(self, (adapter) => |ctx| crate::Context::adapter_features(ctx, adapter))
+    }
+
+    fn adapter_limits(&self, adapter: &Self::AdapterId) -> Limits {
+        dispatch!(self, (adapter) => |ctx| crate::Context::adapter_limits(ctx, adapter))
+    }
+
+    fn device_features(&self, device: &Self::DeviceId) -> Features {
+        dispatch!(self, (device) => |ctx| crate::Context::device_features(ctx, device))
+    }
+
+    fn device_limits(&self, device: &Self::DeviceId) -> Limits {
+        dispatch!(self, (device) => |ctx| crate::Context::device_limits(ctx, device))
+    }
+
+    fn device_create_swap_chain(
+        &self,
+        device: &Self::DeviceId,
+        surface: &Self::SurfaceId,
+        desc: &SwapChainDescriptor,
+    ) -> Self::SwapChainId {
+        dispatch!(self, (device, surface) => |ctx, Wrap| {
+            Wrap(crate::Context::device_create_swap_chain(ctx, device, surface, desc))
+        })
+    }
+
+    fn device_create_shader_module(
+        &self,
+        device: &Self::DeviceId,
+        source: ShaderModuleSource,
+    ) -> Result<Self::ShaderModuleId, CreateError> {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            crate::Context::device_create_shader_module(ctx, device, source).map(Wrap)
+        })
+    }
+
+    fn device_create_bind_group_layout(
+        &self,
+        device: &Self::DeviceId,
+        desc: &BindGroupLayoutDescriptor,
+    ) -> Result<Self::BindGroupLayoutId, CreateError> {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            crate::Context::device_create_bind_group_layout(ctx, device, desc).map(Wrap)
+        })
+    }
+
+    fn device_create_bind_group(
+        &self,
+        device: &Self::DeviceId,
+        desc: &BindGroupDescriptor,
+    ) -> Result<Self::BindGroupId, CreateError> {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            crate::Context::device_create_bind_group(ctx, device, desc).map(Wrap)
+        })
+    }
+
+    fn device_create_pipeline_layout(
+        &self,
+        device: &Self::DeviceId,
+        desc: &PipelineLayoutDescriptor,
+    ) -> Result<Self::PipelineLayoutId, CreateError> {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            crate::Context::device_create_pipeline_layout(ctx, device, desc).map(Wrap)
+        })
+    }
+
+    fn device_create_render_pipeline(
+        &self,
+        device: &Self::DeviceId,
+        desc: &RenderPipelineDescriptor,
+    ) -> Result<Self::RenderPipelineId, CreateError> {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            crate::Context::device_create_render_pipeline(ctx, device, desc).map(Wrap)
+        })
+    }
+
+    fn device_create_compute_pipeline(
+        &self,
+        device: &Self::DeviceId,
+        desc: &ComputePipelineDescriptor,
+    ) -> Result<Self::ComputePipelineId, CreateError> {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            crate::Context::device_create_compute_pipeline(ctx, device, desc).map(Wrap)
+        })
+    }
+
+    fn device_create_buffer(
+        &self,
+        device: &Self::DeviceId,
+        desc: &BufferDescriptor,
+    ) -> Result<Self::BufferId, CreateError> {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            crate::Context::device_create_buffer(ctx, device, desc).map(Wrap)
+        })
+    }
+
+    fn device_create_texture(
+        &self,
+        device: &Self::DeviceId,
+        desc: &TextureDescriptor,
+    ) -> Result<Self::TextureId, CreateError> {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            crate::Context::device_create_texture(ctx, device, desc).map(Wrap)
+        })
+    }
+
+    fn device_create_sampler(
+        &self,
+        device: &Self::DeviceId,
+        desc: &SamplerDescriptor,
+    ) -> Result<Self::SamplerId, CreateError> {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            crate::Context::device_create_sampler(ctx, device, desc).map(Wrap)
+        })
+    }
+
+    fn device_create_command_encoder(
+        &self,
+        device: &Self::DeviceId,
+        desc: &CommandEncoderDescriptor,
+    ) -> Self::CommandEncoderId {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            Wrap(crate::Context::device_create_command_encoder(ctx, device, desc))
+        })
+    }
+
+    fn device_create_render_bundle_encoder(
+        &self,
+        device: &Self::DeviceId,
+        desc: &RenderBundleEncoderDescriptor,
+    ) -> Self::RenderBundleEncoderId {
+        dispatch!(self, (device) => |ctx, Wrap| {
+            Wrap(crate::Context::device_create_render_bundle_encoder(ctx, device, desc))
+        })
+    }
+
+    fn device_drop(&self, device: &Self::DeviceId) {
+        dispatch!(self, (device) => |ctx| crate::Context::device_drop(ctx, device))
+    }
+
+    fn device_poll(&self, device: &Self::DeviceId, maintain: Maintain) {
+        dispatch!(self, (device) => |ctx| crate::Context::device_poll(ctx, device, maintain))
+    }
+
+    fn device_push_error_scope(&self, device: &Self::DeviceId, filter: ErrorFilter) {
+        dispatch!(self, (device) => |ctx| {
+            crate::Context::device_push_error_scope(ctx, device, filter)
+        })
+    }
+
+    fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture {
+        match self {
+            Backend::Real(ctx) => {
+                Either::Left(crate::Context::device_pop_error_scope(ctx, device.real()))
+            }
+            Backend::Mock(ctx) => {
+                Either::Right(crate::Context::device_pop_error_scope(ctx, device.mock()))
+            }
+        }
+    }
+
+    fn device_on_uncaptured_error(
+        &self,
+        device: &Self::DeviceId,
+        handler: Box<dyn Fn(Error) + Send + Sync>,
+    ) {
+        dispatch!(self, (device) => |ctx| {
+            crate::Context::device_on_uncaptured_error(ctx, device, handler)
+        })
+    }
+
+    fn device_capture_error(&self, device: &Self::DeviceId, error: Error) {
+        dispatch!(self, (device) => |ctx| crate::Context::device_capture_error(ctx, device, error))
+    }
+
+    fn device_report_error(&self, device: &Self::DeviceId, error: Error) {
+        dispatch!(self, (device) => |ctx| crate::Context::device_report_error(ctx, device, error))
+    }
+
+    fn buffer_map_async(
+        &self,
+        buffer: &Self::BufferId,
+        mode: MapMode,
+        range: Range<BufferAddress>,
+    ) -> Self::MapAsyncFuture {
+        match self {
+            Backend::Real(ctx) => Either::Left(crate::Context::buffer_map_async(
+                ctx,
+                buffer.real(),
+                mode,
+                range,
+            )),
+            Backend::Mock(ctx) => Either::Right(crate::Context::buffer_map_async(
+                ctx,
+                buffer.mock(),
+                mode,
+                range,
+            )),
+        }
+    }
+
+    fn buffer_get_mapped_range(
+        &self,
+        buffer: &Self::BufferId,
+        sub_range: Range<BufferAddress>,
+    ) -> &[u8] {
+        dispatch!(self, (buffer) => |ctx| {
+            crate::Context::buffer_get_mapped_range(ctx, buffer, sub_range)
+        })
+    }
+
+    fn buffer_get_mapped_range_mut(
+        &self,
+        buffer: &Self::BufferId,
+        sub_range: Range<BufferAddress>,
+    ) -> &mut [u8] {
+        dispatch!(self, (buffer) => |ctx| {
+            crate::Context::buffer_get_mapped_range_mut(ctx, buffer, sub_range)
+        })
+    }
+
+    fn buffer_unmap(&self, buffer: &Self::BufferId) {
+        dispatch!(self, (buffer) => |ctx| crate::Context::buffer_unmap(ctx, buffer))
+    }
+
+    fn swap_chain_get_next_texture(
+        &self,
+        swap_chain: &Self::SwapChainId,
+    ) -> (
+        Option<Self::TextureViewId>,
+        SwapChainStatus,
+        Self::SwapChainOutputDetail,
+    ) {
+        dispatch!(self, (swap_chain) => |ctx, Wrap| {
+            let (view, status, detail) =
+                crate::Context::swap_chain_get_next_texture(ctx, swap_chain);
+            (view.map(Wrap), status, Wrap(detail))
+        })
+    }
+
+    fn swap_chain_present(&self, view: &Self::TextureViewId, detail: &Self::SwapChainOutputDetail) {
+        dispatch!(self, (view, detail) => |ctx| {
+            crate::Context::swap_chain_present(ctx, view, detail)
+        })
+    }
+
+    fn texture_create_view(
+        &self,
+        texture: &Self::TextureId,
+        desc: Option<&TextureViewDescriptor>,
+    ) -> Self::TextureViewId {
+        dispatch!(self, (texture) => |ctx, Wrap| {
+            Wrap(crate::Context::texture_create_view(ctx, texture, desc))
+        })
+    }
+
+    fn texture_drop(&self, texture: &Self::TextureId) {
+        dispatch!(self, (texture) => |ctx| crate::Context::texture_drop(ctx, texture))
+    }
+    fn texture_view_drop(&self, texture_view: &Self::TextureViewId) {
+        dispatch!(self, (texture_view) => |ctx| {
+            crate::Context::texture_view_drop(ctx, texture_view)
+        })
+    }
+    fn sampler_drop(&self, sampler: &Self::SamplerId) {
+        dispatch!(self, (sampler) => |ctx| crate::Context::sampler_drop(ctx, sampler))
+    }
+    fn buffer_drop(&self, buffer: &Self::BufferId) {
+        dispatch!(self, (buffer) => |ctx| crate::Context::buffer_drop(ctx, buffer))
+    }
+    fn bind_group_drop(&self, bind_group: &Self::BindGroupId) {
+        dispatch!(self, (bind_group) => |ctx| crate::Context::bind_group_drop(ctx, bind_group))
+    }
+    fn bind_group_layout_drop(&self, bind_group_layout: &Self::BindGroupLayoutId) {
+        dispatch!(self, (bind_group_layout) => |ctx| {
+            crate::Context::bind_group_layout_drop(ctx, bind_group_layout)
+        })
+    }
+    fn pipeline_layout_drop(&self, pipeline_layout: &Self::PipelineLayoutId) {
+        dispatch!(self, (pipeline_layout) => |ctx| {
+            crate::Context::pipeline_layout_drop(ctx, pipeline_layout)
+        })
+    }
+    fn shader_module_drop(&self, shader_module: &Self::ShaderModuleId) {
+        dispatch!(self, (shader_module) => |ctx| {
+            crate::Context::shader_module_drop(ctx, shader_module)
+        })
+    }
+    fn command_buffer_drop(&self, command_buffer: &Self::CommandBufferId) {
+        dispatch!(self, (command_buffer) => |ctx| {
+            crate::Context::command_buffer_drop(ctx, command_buffer)
+        })
+    }
+    fn render_bundle_drop(&self, render_bundle: &Self::RenderBundleId) {
+        dispatch!(self, (render_bundle) => |ctx| {
+            crate::Context::render_bundle_drop(ctx, render_bundle)
+        })
+    }
+    fn compute_pipeline_drop(&self, pipeline: &Self::ComputePipelineId) {
+        dispatch!(self, (pipeline) => |ctx| crate::Context::compute_pipeline_drop(ctx, pipeline))
+    }
+    fn render_pipeline_drop(&self, pipeline: &Self::RenderPipelineId) {
+        dispatch!(self, (pipeline) => |ctx| crate::Context::render_pipeline_drop(ctx, pipeline))
+    }
+
+    fn command_encoder_copy_buffer_to_buffer(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        source: &Self::BufferId,
+        source_offset: BufferAddress,
+        destination: &Self::BufferId,
+        destination_offset: BufferAddress,
+        copy_size: BufferAddress,
+    ) {
+        dispatch!(self, (encoder, source, destination) => |ctx| {
+            crate::Context::command_encoder_copy_buffer_to_buffer(
+                ctx,
+                encoder,
+                source,
+                source_offset,
+                destination,
+                destination_offset,
+                copy_size,
+            )
+        })
+    }
+
+    fn command_encoder_copy_buffer_to_texture(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        source: BufferCopyView,
+        destination: TextureCopyView,
+        copy_size: Extent3d,
+    ) {
+        dispatch!(self, (encoder) => |ctx| {
+            crate::Context::command_encoder_copy_buffer_to_texture(
+                ctx,
+                encoder,
+                source,
+                destination,
+                copy_size,
+            )
+        })
+    }
+
+    fn command_encoder_copy_texture_to_buffer(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        source: TextureCopyView,
+        destination: BufferCopyView,
+        copy_size: Extent3d,
+    ) {
+        dispatch!(self, (encoder) => |ctx| {
+            crate::Context::command_encoder_copy_texture_to_buffer(
+                ctx,
+                encoder,
+                source,
+                destination,
+                copy_size,
+            )
+        })
+    }
+
+    fn command_encoder_copy_texture_to_texture(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        source: TextureCopyView,
+        destination: TextureCopyView,
+        copy_size: Extent3d,
+    ) {
+        dispatch!(self, (encoder) => |ctx| {
+            crate::Context::command_encoder_copy_texture_to_texture(
+                ctx,
+                encoder,
+                source,
+                destination,
+                copy_size,
+            )
+        })
+    }
+
+    fn command_encoder_begin_compute_pass(
+        &self,
+        encoder: &Self::CommandEncoderId,
+    ) -> Self::ComputePassId {
+        dispatch!(self, (encoder) => |ctx, Wrap| {
+            Wrap(crate::Context::command_encoder_begin_compute_pass(ctx, encoder))
+        })
+    }
+
+    fn command_encoder_end_compute_pass(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        pass: &mut Self::ComputePassId,
+    ) {
+        match self {
+            Backend::Real(ctx) => crate::Context::command_encoder_end_compute_pass(
+                ctx,
+                encoder.real(),
+                pass.real_mut(),
+            ),
+            Backend::Mock(ctx) => crate::Context::command_encoder_end_compute_pass(
+                ctx,
+                encoder.mock(),
+                pass.mock_mut(),
+            ),
+        }
+    }
+
+    fn command_encoder_begin_render_pass<'a>(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        desc: &RenderPassDescriptor<'a, '_>,
+    ) -> Self::RenderPassId {
+        dispatch!(self, (encoder) => |ctx, Wrap| {
+            Wrap(crate::Context::command_encoder_begin_render_pass(ctx, encoder, desc))
+        })
+    }
+
+    fn command_encoder_end_render_pass(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        pass: &mut Self::RenderPassId,
+    ) {
+        match self {
+            Backend::Real(ctx) => crate::Context::command_encoder_end_render_pass(
+                ctx,
+                encoder.real(),
+                pass.real_mut(),
+            ),
+            Backend::Mock(ctx) => crate::Context::command_encoder_end_render_pass(
+                ctx,
+                encoder.mock(),
+                pass.mock_mut(),
+            ),
+        }
+    }
+
+    fn command_encoder_finish(&self, encoder: &Self::CommandEncoderId) -> Self::CommandBufferId {
+        dispatch!(self, (encoder) => |ctx, Wrap| {
+            Wrap(crate::Context::command_encoder_finish(ctx, encoder))
+        })
+    }
+
+    fn render_bundle_encoder_finish(
+        &self,
+        encoder: Self::RenderBundleEncoderId,
+        desc: &RenderBundleDescriptor,
+    ) -> Self::RenderBundleId {
+        match self {
+            Backend::Real(ctx) => Backend::Real(crate::Context::render_bundle_encoder_finish(
+                ctx,
+                encoder.into_real(),
+                desc,
+            )),
+            Backend::Mock(ctx) => Backend::Mock(crate::Context::render_bundle_encoder_finish(
+                ctx,
+                encoder.into_mock(),
+                desc,
+            )),
+        }
+    }
+
+    fn queue_write_buffer(
+        &self,
+        queue: &Self::QueueId,
+        buffer: &Self::BufferId,
+        offset: BufferAddress,
+        data: &[u8],
+    ) {
+        dispatch!(self, (queue, buffer) => |ctx| {
+            crate::Context::queue_write_buffer(ctx, queue, buffer, offset, data)
+        })
+    }
+
+    fn queue_write_texture(
+        &self,
+        queue: &Self::QueueId,
+        texture: TextureCopyView,
+        data: &[u8],
+        data_layout: TextureDataLayout,
+        size: Extent3d,
+    ) {
+        dispatch!(self, (queue) => |ctx| {
+            crate::Context::queue_write_texture(ctx, queue, texture, data, data_layout, size)
+        })
+    }
+
+    fn queue_submit<I: Iterator<Item = Self::CommandBufferId>>(
+        &self,
+        queue: &Self::QueueId,
+        command_buffers: I,
+    ) {
+        match self {
+            Backend::Real(ctx) => crate::Context::queue_submit(
+                ctx,
+                queue.real(),
+                command_buffers.map(Backend::into_real),
+            ),
+            Backend::Mock(ctx) => crate::Context::queue_submit(
+                ctx,
+                queue.mock(),
+                command_buffers.map(Backend::into_mock),
+            ),
+        }
+    }
+}
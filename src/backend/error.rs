@@ -0,0 +1,92 @@
+use parking_lot::Mutex;
+use std::{fmt, sync::Arc};
+
+pub(crate) type ErrorSink = Arc<Mutex<ErrorSinkRaw>>;
+
+#[derive(Debug)]
+struct ErrorScope {
+    error: Option<crate::Error>,
+    filter: crate::ErrorFilter,
+}
+
+/// Error scope stack and uncaptured error handler of a device.
+pub(crate) struct ErrorSinkRaw {
+    scopes: Vec<ErrorScope>,
+    uncaptured_handler: Arc<dyn Fn(crate::Error) + Send + Sync>,
+}
+
+impl ErrorSinkRaw {
+    pub(crate) fn new_sink() -> ErrorSink {
+        Arc::new(Mutex::new(ErrorSinkRaw {
+            scopes: Vec::new(),
+            uncaptured_handler: Arc::new(default_error_handler),
+        }))
+    }
+
+    pub(crate) fn push_scope(&mut self, filter: crate::ErrorFilter) {
+        self.scopes.push(ErrorScope {
+            error: None,
+            filter,
+        });
+    }
+
+    pub(crate) fn pop_scope(&mut self) -> Option<crate::Error> {
+        self.scopes
+            .pop()
+            .expect("Called pop_error_scope without a matching push_error_scope")
+            .error
+    }
+
+    pub(crate) fn set_uncaptured_handler(
+        &mut self,
+        handler: Arc<dyn Fn(crate::Error) + Send + Sync>,
+    ) {
+        self.uncaptured_handler = handler;
+    }
+
+    /// Stores `error` in the innermost scope with a matching filter.
+    ///
+    /// Returns the error back if no scope captured it.
+    pub(crate) fn capture(&mut self, error: crate::Error) -> Option<crate::Error> {
+        let filter = error.filter();
+        match self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.filter == filter)
+        {
+            Some(scope) => {
+                if scope.error.is_none() {
+                    scope.error = Some(error);
+                }
+                None
+            }
+            None => Some(error),
+        }
+    }
+}
+
+impl fmt::Debug for ErrorSinkRaw {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ErrorSinkRaw")
+            .field("scopes", &self.scopes)
+            .finish()
+    }
+}
+
+/// Captures `error` in an error scope of `sink`, or passes it to the uncaptured error handler.
+///
+/// The handler is called after the lock on `sink` is released, so it may use the device.
+pub(crate) fn handle_error(sink: &ErrorSink, error: crate::Error) {
+    let mut sink = sink.lock();
+    if let Some(error) = sink.capture(error) {
+        let handler = Arc::clone(&sink.uncaptured_handler);
+        drop(sink);
+        handler(error);
+    }
+}
+
+fn default_error_handler(error: crate::Error) {
+    tracing::error!("Handling wgpu errors as fatal by default: {}", error);
+    panic!("wgpu error: {}", error);
+}
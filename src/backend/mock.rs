@@ -0,0 +1,1540 @@
+use crate::{
+    backend::error::{self, ErrorSink, ErrorSinkRaw},
+    mock::{Command, ResourceId, ResourceKind},
+    util::{self, texture_format_info},
+    BindGroupDescriptor, BindGroupLayoutDescriptor, BufferAddress, BufferDescriptor, BufferUsage,
+    CommandEncoderDescriptor, ComputePipelineDescriptor, CreateError, DynamicOffset, Features,
+    Limits, MapMode, PipelineLayoutDescriptor, RenderPipelineDescriptor, SamplerDescriptor,
+    ShaderModuleSource, SwapChainStatus, TextureDescriptor, TextureViewDescriptor,
+};
+
+use futures::future::{ready, Ready};
+use parking_lot::Mutex;
+use std::{
+    collections::HashSet,
+    fmt,
+    ops::Range,
+    slice,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// Context of the mock backend.
+///
+/// Keeps track of the resources that are alive. All other state lives in the handles.
+#[derive(Debug)]
+pub(crate) struct Context {
+    next_index: AtomicU64,
+    live: Mutex<HashSet<ResourceId>>,
+}
+
+impl Context {
+    fn register(&self, kind: ResourceKind) -> ResourceId {
+        let id = ResourceId {
+            kind,
+            index: self.next_index.fetch_add(1, Ordering::Relaxed),
+        };
+        self.live.lock().insert(id);
+        id
+    }
+
+    fn unregister(&self, id: ResourceId) {
+        self.live.lock().remove(&id);
+    }
+
+    pub(crate) fn live_resources(&self) -> Vec<ResourceId> {
+        let mut resources = self.live.lock().iter().cloned().collect::<Vec<_>>();
+        resources.sort_by_key(|id| id.index);
+        resources
+    }
+}
+
+type CommandLog = Arc<Mutex<Vec<Command>>>;
+
+#[derive(Debug)]
+pub(crate) struct Device {
+    features: Features,
+    limits: Limits,
+    error_sink: ErrorSink,
+    commands: CommandLog,
+}
+
+#[derive(Debug)]
+pub(crate) struct Queue {
+    error_sink: ErrorSink,
+    pub(crate) commands: CommandLog,
+}
+
+/// Resource without any state besides its identifier.
+#[derive(Debug)]
+pub(crate) struct Handle {
+    pub(crate) id: ResourceId,
+}
+
+pub(crate) struct Buffer {
+    pub(crate) id: ResourceId,
+    size: BufferAddress,
+    usage: BufferUsage,
+    data: Mutex<Vec<u8>>,
+    mapped: AtomicBool,
+}
+
+impl Buffer {
+    /// Checks that the buffer can be accessed by the queue with the given usage.
+    fn validate_usage(&self, usage: BufferUsage) -> Result<(), String> {
+        if !self.usage.contains(usage) {
+            return Err(format!("buffer {:?} is missing usage {:?}", self.id, usage));
+        }
+        if self.mapped.load(Ordering::Acquire) {
+            return Err(format!("buffer {:?} is still mapped", self.id));
+        }
+        Ok(())
+    }
+
+    /// Checks that `offset..offset + size` can be accessed with the given usage.
+    fn validate_range(
+        &self,
+        offset: BufferAddress,
+        size: BufferAddress,
+        usage: BufferUsage,
+    ) -> Result<(), String> {
+        self.validate_usage(usage)?;
+        if offset % crate::COPY_BUFFER_ALIGNMENT != 0 || size % crate::COPY_BUFFER_ALIGNMENT != 0 {
+            return Err(format!(
+                "range {}..{} of buffer {:?} is not aligned to {}",
+                offset,
+                offset + size,
+                self.id,
+                crate::COPY_BUFFER_ALIGNMENT
+            ));
+        }
+        if offset + size > self.size {
+            return Err(format!(
+                "range {}..{} is out of bounds of buffer {:?} of size {}",
+                offset,
+                offset + size,
+                self.id,
+                self.size
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns a pointer to the start of `sub_range` of the mapped buffer.
+    fn mapped_ptr(&self, sub_range: &Range<BufferAddress>) -> *mut u8 {
+        assert!(
+            self.mapped.load(Ordering::Acquire),
+            "Buffer {:?} is not mapped",
+            self.id
+        );
+        assert!(sub_range.start <= sub_range.end && sub_range.end <= self.size);
+        // The storage is never reallocated, so the mapped range stays valid until the
+        // buffer is dropped. Queue operations refuse to touch mapped buffers.
+        // `Vec::as_mut_ptr` doesn't create a reference to the whole storage, so views of
+        // the ranges handed out by `MapContext` don't invalidate each other.
+        unsafe { self.data.lock().as_mut_ptr().add(sub_range.start as usize) }
+    }
+}
+
+impl fmt::Debug for Buffer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Buffer")
+            .field("id", &self.id)
+            .field("size", &self.size)
+            .field("usage", &self.usage)
+            .finish()
+    }
+}
+
+pub(crate) struct Texture {
+    pub(crate) id: ResourceId,
+    size: wgt::Extent3d,
+    mip_level_count: u32,
+    sample_count: u32,
+    dimension: wgt::TextureDimension,
+    format: wgt::TextureFormat,
+    usage: wgt::TextureUsage,
+    /// Texels of each mip level, with array layers (or depth slices) one after the other.
+    mips: Mutex<Vec<Box<[u8]>>>,
+}
+
+impl Texture {
+    fn new(id: ResourceId, desc: &TextureDescriptor) -> Self {
+        let mut texture = Texture {
+            id,
+            size: desc.size,
+            mip_level_count: desc.mip_level_count,
+            sample_count: desc.sample_count,
+            dimension: desc.dimension,
+            format: desc.format,
+            usage: desc.usage,
+            mips: Mutex::new(Vec::new()),
+        };
+        let texel_size = texture_format_info(desc.format).block_size as usize;
+        let mips = (0..desc.mip_level_count)
+            .map(|level| {
+                let extent = texture.mip_extent(level);
+                let texel_count =
+                    extent.width as usize * extent.height as usize * extent.depth as usize;
+                vec![0; texel_count * texel_size].into_boxed_slice()
+            })
+            .collect();
+        *texture.mips.get_mut() = mips;
+        texture
+    }
+
+    /// Extent of a mip level, with array layers counted in `depth`.
+    fn mip_extent(&self, level: u32) -> wgt::Extent3d {
+        util::mip_extent(self.size, self.dimension, level)
+    }
+}
+
+impl fmt::Debug for Texture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Texture")
+            .field("id", &self.id)
+            .field("size", &self.size)
+            .field("format", &self.format)
+            .finish()
+    }
+}
+
+/// Texture side of a copy.
+#[derive(Debug)]
+struct TextureRegion {
+    texture: Arc<Texture>,
+    mip_level: u32,
+    origin: wgt::Origin3d,
+}
+
+impl TextureRegion {
+    fn new(view: crate::TextureCopyView) -> Self {
+        TextureRegion {
+            texture: Arc::clone(view.texture.id.mock()),
+            mip_level: view.mip_level,
+            origin: view.origin,
+        }
+    }
+
+    fn row_size(&self, size: wgt::Extent3d) -> usize {
+        size.width as usize * texture_format_info(self.texture.format).block_size as usize
+    }
+
+    /// Checks that a copy of `size` can access the region with the given usage.
+    fn validate(&self, size: wgt::Extent3d, usage: wgt::TextureUsage) -> Result<(), String> {
+        let texture = &self.texture;
+        if !texture.usage.contains(usage) {
+            return Err(format!(
+                "texture {:?} is missing usage {:?}",
+                texture.id, usage
+            ));
+        }
+        if texture.sample_count > 1 {
+            return Err(format!(
+                "multisampled texture {:?} cannot be copied",
+                texture.id
+            ));
+        }
+        if !texture_format_info(texture.format).copyable {
+            return Err(format!(
+                "texture {:?} of format {:?} cannot be copied",
+                texture.id, texture.format
+            ));
+        }
+        if self.mip_level >= texture.mip_level_count {
+            return Err(format!(
+                "mip level {} is out of bounds of texture {:?} with {} levels",
+                self.mip_level, texture.id, texture.mip_level_count
+            ));
+        }
+        let extent = texture.mip_extent(self.mip_level);
+        let fits = |origin: u32, size: u32, limit: u32| origin as u64 + size as u64 <= limit as u64;
+        if !fits(self.origin.x, size.width, extent.width)
+            || !fits(self.origin.y, size.height, extent.height)
+            || !fits(self.origin.z, size.depth, extent.depth)
+        {
+            return Err(format!(
+                "copy of {:?} at {:?} is out of bounds of mip level {} of texture {:?} with extent {:?}",
+                size, self.origin, self.mip_level, texture.id, extent
+            ));
+        }
+        Ok(())
+    }
+
+    /// Byte ranges of the rows of a copy of `size` within the mip level.
+    fn rows(&self, size: wgt::Extent3d) -> impl Iterator<Item = Range<usize>> {
+        let extent = self.texture.mip_extent(self.mip_level);
+        let texel_size = texture_format_info(self.texture.format).block_size as usize;
+        let row_size = self.row_size(size);
+        let origin = self.origin;
+        (0..size.depth).flat_map(move |z| {
+            (0..size.height).map(move |y| {
+                let row =
+                    (origin.z + z) as usize * extent.height as usize + (origin.y + y) as usize;
+                let start = (row * extent.width as usize + origin.x as usize) * texel_size;
+                start..start + row_size
+            })
+        })
+    }
+
+    /// Reads the texels of a copy of `size`, tightly packed.
+    fn read(&self, size: wgt::Extent3d) -> Vec<u8> {
+        let mips = self.texture.mips.lock();
+        let mip = &mips[self.mip_level as usize];
+        self.rows(size)
+            .flat_map(|range| mip[range].iter().cloned())
+            .collect()
+    }
+
+    /// Writes tightly packed texels of a copy of `size`.
+    fn write(&self, size: wgt::Extent3d, texels: &[u8]) {
+        let row_size = self.row_size(size);
+        if row_size == 0 {
+            return;
+        }
+        let mut mips = self.texture.mips.lock();
+        let mip = &mut mips[self.mip_level as usize];
+        for (range, row) in self.rows(size).zip(texels.chunks(row_size)) {
+            mip[range].copy_from_slice(row);
+        }
+    }
+}
+
+/// Byte ranges of the rows of a copy of `size` in linear memory.
+fn linear_rows(
+    layout: &wgt::TextureDataLayout,
+    size: wgt::Extent3d,
+    row_size: usize,
+) -> impl Iterator<Item = Range<usize>> {
+    let offset = layout.offset as usize;
+    let bytes_per_row = layout.bytes_per_row as usize;
+    let rows_per_image = match layout.rows_per_image {
+        0 => size.height,
+        rows => rows,
+    } as usize;
+    (0..size.depth as usize).flat_map(move |z| {
+        (0..size.height as usize).map(move |y| {
+            let start = offset + (z * rows_per_image + y) * bytes_per_row;
+            start..start + row_size
+        })
+    })
+}
+
+/// Checks that a copy of `size` described by `layout` fits in `data_size` bytes.
+fn validate_linear(
+    layout: &wgt::TextureDataLayout,
+    size: wgt::Extent3d,
+    row_size: usize,
+    data_size: BufferAddress,
+    row_alignment: u32,
+) -> Result<(), String> {
+    if layout.bytes_per_row % row_alignment != 0 {
+        return Err(format!(
+            "bytes per row {} is not a multiple of {}",
+            layout.bytes_per_row, row_alignment
+        ));
+    }
+    if (size.height > 1 || size.depth > 1) && (layout.bytes_per_row as usize) < row_size {
+        return Err(format!(
+            "bytes per row {} is less than the {} bytes of a row",
+            layout.bytes_per_row, row_size
+        ));
+    }
+    if layout.rows_per_image != 0 && layout.rows_per_image < size.height {
+        return Err(format!(
+            "rows per image {} is less than the copy height {}",
+            layout.rows_per_image, size.height
+        ));
+    }
+    let end = linear_rows(layout, size, row_size)
+        .last()
+        .map_or(layout.offset, |range| range.end as BufferAddress);
+    if end > data_size {
+        return Err(format!(
+            "copy of {:?} with {:?} needs {} bytes, but only {} are available",
+            size, layout, end, data_size
+        ));
+    }
+    Ok(())
+}
+
+/// Buffer side of a copy.
+#[derive(Debug)]
+struct BufferRegion {
+    buffer: Arc<Buffer>,
+    layout: wgt::TextureDataLayout,
+}
+
+impl BufferRegion {
+    fn new(view: crate::BufferCopyView) -> Self {
+        BufferRegion {
+            buffer: Arc::clone(view.buffer.id.mock()),
+            layout: view.layout,
+        }
+    }
+
+    fn validate(
+        &self,
+        size: wgt::Extent3d,
+        row_size: usize,
+        usage: BufferUsage,
+    ) -> Result<(), String> {
+        self.buffer.validate_usage(usage)?;
+        validate_linear(
+            &self.layout,
+            size,
+            row_size,
+            self.buffer.size,
+            crate::COPY_BYTES_PER_ROW_ALIGNMENT,
+        )
+    }
+}
+
+fn copy_buffer_to_texture(
+    source: &BufferRegion,
+    destination: &TextureRegion,
+    size: wgt::Extent3d,
+) -> Result<(), String> {
+    let row_size = destination.row_size(size);
+    source.validate(size, row_size, BufferUsage::COPY_SRC)?;
+    destination.validate(size, wgt::TextureUsage::COPY_DST)?;
+    let data = source.buffer.data.lock();
+    let texels = linear_rows(&source.layout, size, row_size)
+        .flat_map(|range| data[range].iter().cloned())
+        .collect::<Vec<_>>();
+    destination.write(size, &texels);
+    Ok(())
+}
+
+fn copy_texture_to_buffer(
+    source: &TextureRegion,
+    destination: &BufferRegion,
+    size: wgt::Extent3d,
+) -> Result<(), String> {
+    let row_size = source.row_size(size);
+    source.validate(size, wgt::TextureUsage::COPY_SRC)?;
+    destination.validate(size, row_size, BufferUsage::COPY_DST)?;
+    if row_size == 0 {
+        return Ok(());
+    }
+    let texels = source.read(size);
+    let mut data = destination.buffer.data.lock();
+    for (range, row) in
+        linear_rows(&destination.layout, size, row_size).zip(texels.chunks(row_size))
+    {
+        data[range].copy_from_slice(row);
+    }
+    Ok(())
+}
+
+fn copy_texture_to_texture(
+    source: &TextureRegion,
+    destination: &TextureRegion,
+    size: wgt::Extent3d,
+) -> Result<(), String> {
+    source.validate(size, wgt::TextureUsage::COPY_SRC)?;
+    destination.validate(size, wgt::TextureUsage::COPY_DST)?;
+    if source.texture.format != destination.texture.format {
+        return Err(format!(
+            "cannot copy from texture {:?} of format {:?} to texture {:?} of format {:?}",
+            source.texture.id,
+            source.texture.format,
+            destination.texture.id,
+            destination.texture.format
+        ));
+    }
+    let texels = source.read(size);
+    destination.write(size, &texels);
+    Ok(())
+}
+
+#[derive(Debug)]
+pub(crate) struct SwapChain {
+    commands: CommandLog,
+}
+
+/// Recorded commands of a pass or a render bundle.
+#[derive(Debug, Default)]
+pub(crate) struct CommandRecorder {
+    commands: Vec<Command>,
+}
+
+#[derive(Debug)]
+pub(crate) struct RenderBundle {
+    pub(crate) id: ResourceId,
+    commands: Vec<Command>,
+}
+
+/// Command encoded into a command buffer, executed on submission.
+#[derive(Debug)]
+enum Operation {
+    Record(Command),
+    CopyBufferToBuffer {
+        source: Arc<Buffer>,
+        source_offset: BufferAddress,
+        destination: Arc<Buffer>,
+        destination_offset: BufferAddress,
+        size: BufferAddress,
+    },
+    CopyBufferToTexture {
+        source: BufferRegion,
+        destination: TextureRegion,
+        size: wgt::Extent3d,
+    },
+    CopyTextureToBuffer {
+        source: TextureRegion,
+        destination: BufferRegion,
+        size: wgt::Extent3d,
+    },
+    CopyTextureToTexture {
+        source: TextureRegion,
+        destination: TextureRegion,
+        size: wgt::Extent3d,
+    },
+}
+
+#[derive(Debug)]
+pub(crate) struct CommandEncoder {
+    operations: Mutex<Vec<Operation>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct CommandBuffer {
+    operations: Vec<Operation>,
+}
+
+fn handle_error(sink: &ErrorSink, description: String) {
+    error::handle_error(sink, crate::Error::Validation { description });
+}
+
+fn copy_buffer_to_buffer(
+    source: &Buffer,
+    source_offset: BufferAddress,
+    destination: &Buffer,
+    destination_offset: BufferAddress,
+    size: BufferAddress,
+) -> Result<(), String> {
+    source.validate_range(source_offset, size, BufferUsage::COPY_SRC)?;
+    destination.validate_range(destination_offset, size, BufferUsage::COPY_DST)?;
+    let source_range = source_offset as usize..(source_offset + size) as usize;
+    let destination_offset = destination_offset as usize;
+    if source.id == destination.id {
+        source
+            .data
+            .lock()
+            .copy_within(source_range, destination_offset);
+    } else {
+        let source_data = source.data.lock();
+        destination.data.lock()[destination_offset..destination_offset + size as usize]
+            .copy_from_slice(&source_data[source_range]);
+    }
+    Ok(())
+}
+
+impl crate::ComputePassInner<Context> for CommandRecorder {
+    fn set_pipeline(&mut self, pipeline: &Handle) {
+        self.commands.push(Command::SetPipeline(pipeline.id));
+    }
+    fn set_bind_group(&mut self, index: u32, bind_group: &Handle, offsets: &[DynamicOffset]) {
+        self.commands.push(Command::SetBindGroup {
+            index,
+            bind_group: bind_group.id,
+            offsets: offsets.to_vec(),
+        });
+    }
+    fn set_push_constants(&mut self, offset: u32, data: &[u32]) {
+        self.commands.push(Command::SetPushConstants {
+            stages: wgt::ShaderStage::COMPUTE,
+            offset,
+            data: data.to_vec(),
+        });
+    }
+    fn dispatch(&mut self, x: u32, y: u32, z: u32) {
+        self.commands.push(Command::Dispatch { x, y, z });
+    }
+    fn dispatch_indirect(&mut self, indirect_buffer: &Arc<Buffer>, indirect_offset: BufferAddress) {
+        self.commands.push(Command::DispatchIndirect {
+            buffer: indirect_buffer.id,
+            offset: indirect_offset,
+        });
+    }
+}
+
+impl crate::RenderInner<Context> for CommandRecorder {
+    fn set_pipeline(&mut self, pipeline: &Handle) {
+        self.commands.push(Command::SetPipeline(pipeline.id));
+    }
+    fn set_bind_group(&mut self, index: u32, bind_group: &Handle, offsets: &[DynamicOffset]) {
+        self.commands.push(Command::SetBindGroup {
+            index,
+            bind_group: bind_group.id,
+            offsets: offsets.to_vec(),
+        });
+    }
+    fn set_index_buffer(
+        &mut self,
+        buffer: &Arc<Buffer>,
+        offset: BufferAddress,
+        size: Option<wgt::BufferSize>,
+    ) {
+        self.commands.push(Command::SetIndexBuffer {
+            buffer: buffer.id,
+            offset,
+            size,
+        });
+    }
+    fn set_vertex_buffer(
+        &mut self,
+        slot: u32,
+        buffer: &Arc<Buffer>,
+        offset: BufferAddress,
+        size: Option<wgt::BufferSize>,
+    ) {
+        self.commands.push(Command::SetVertexBuffer {
+            slot,
+            buffer: buffer.id,
+            offset,
+            size,
+        });
+    }
+    fn set_push_constants(&mut self, stages: wgt::ShaderStage, offset: u32, data: &[u32]) {
+        self.commands.push(Command::SetPushConstants {
+            stages,
+            offset,
+            data: data.to_vec(),
+        });
+    }
+    fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        self.commands.push(Command::Draw {
+            vertices,
+            instances,
+        });
+    }
+    fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
+        self.commands.push(Command::DrawIndexed {
+            indices,
+            base_vertex,
+            instances,
+        });
+    }
+    fn draw_indirect(&mut self, indirect_buffer: &Arc<Buffer>, indirect_offset: BufferAddress) {
+        self.commands.push(Command::DrawIndirect {
+            buffer: indirect_buffer.id,
+            offset: indirect_offset,
+        });
+    }
+    fn draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+    ) {
+        self.commands.push(Command::DrawIndexedIndirect {
+            buffer: indirect_buffer.id,
+            offset: indirect_offset,
+        });
+    }
+    fn multi_draw_indirect(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        self.commands.push(Command::MultiDrawIndirect {
+            buffer: indirect_buffer.id,
+            offset: indirect_offset,
+            count,
+        });
+    }
+    fn multi_draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        self.commands.push(Command::MultiDrawIndexedIndirect {
+            buffer: indirect_buffer.id,
+            offset: indirect_offset,
+            count,
+        });
+    }
+}
+
+impl crate::RenderPassInner<Context> for CommandRecorder {
+    fn set_blend_color(&mut self, color: wgt::Color) {
+        self.commands.push(Command::SetBlendColor(color));
+    }
+    fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.commands.push(Command::SetScissorRect {
+            x,
+            y,
+            width,
+            height,
+        });
+    }
+    fn set_viewport(
+        &mut self,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        min_depth: f32,
+        max_depth: f32,
+    ) {
+        self.commands.push(Command::SetViewport {
+            x,
+            y,
+            width,
+            height,
+            min_depth,
+            max_depth,
+        });
+    }
+    fn set_stencil_reference(&mut self, reference: u32) {
+        self.commands.push(Command::SetStencilReference(reference));
+    }
+    fn insert_debug_marker(&mut self, label: &str) {
+        self.commands
+            .push(Command::InsertDebugMarker(label.to_string()));
+    }
+    fn push_debug_group(&mut self, group_label: &str) {
+        self.commands
+            .push(Command::PushDebugGroup(group_label.to_string()));
+    }
+    fn pop_debug_group(&mut self) {
+        self.commands.push(Command::PopDebugGroup);
+    }
+    fn execute_bundles<'a, I: Iterator<Item = &'a RenderBundle>>(&mut self, render_bundles: I) {
+        for bundle in render_bundles {
+            self.commands.push(Command::ExecuteBundle(bundle.id));
+            self.commands.extend(bundle.commands.iter().cloned());
+        }
+    }
+    fn multi_draw_indirect_count(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+        count_buffer: &Arc<Buffer>,
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        self.commands.push(Command::MultiDrawIndirectCount {
+            buffer: indirect_buffer.id,
+            offset: indirect_offset,
+            count_buffer: count_buffer.id,
+            count_offset: count_buffer_offset,
+            max_count,
+        });
+    }
+    fn multi_draw_indexed_indirect_count(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+        count_buffer: &Arc<Buffer>,
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        self.commands.push(Command::MultiDrawIndexedIndirectCount {
+            buffer: indirect_buffer.id,
+            offset: indirect_offset,
+            count_buffer: count_buffer.id,
+            count_offset: count_buffer_offset,
+            max_count,
+        });
+    }
+}
+
+impl crate::Context for Context {
+    type AdapterId = ();
+    type DeviceId = Device;
+    type QueueId = Queue;
+    type ShaderModuleId = Handle;
+    type BindGroupLayoutId = Handle;
+    type BindGroupId = Handle;
+    type TextureViewId = Handle;
+    type SamplerId = Handle;
+    type BufferId = Arc<Buffer>;
+    type TextureId = Arc<Texture>;
+    type PipelineLayoutId = Handle;
+    type RenderPipelineId = Handle;
+    type ComputePipelineId = Handle;
+    type CommandEncoderId = CommandEncoder;
+    type ComputePassId = CommandRecorder;
+    type RenderPassId = CommandRecorder;
+    type CommandBufferId = CommandBuffer;
+    type RenderBundleEncoderId = CommandRecorder;
+    type RenderBundleId = RenderBundle;
+    type SurfaceId = ();
+    type SwapChainId = SwapChain;
+
+    type SwapChainOutputDetail = CommandLog;
+
+    type RequestAdapterFuture = Ready<Option<Self::AdapterId>>;
+    type RequestDeviceFuture =
+        Ready<Result<(Self::DeviceId, Self::QueueId), crate::RequestDeviceError>>;
+    type MapAsyncFuture = Ready<Result<(), crate::BufferAsyncError>>;
+    type PopErrorScopeFuture = Ready<Option<crate::Error>>;
+
+    fn init(_backends: wgt::BackendBit) -> Self {
+        Context {
+            next_index: AtomicU64::new(0),
+            live: Mutex::new(HashSet::new()),
+        }
+    }
+
+    fn instance_create_surface(
+        &self,
+        _handle: &impl raw_window_handle::HasRawWindowHandle,
+    ) -> Self::SurfaceId {
+    }
+
+    fn instance_request_adapter(
+        &self,
+        _options: &crate::RequestAdapterOptions,
+    ) -> Self::RequestAdapterFuture {
+        ready(Some(()))
+    }
+
+    fn adapter_request_device(
+        &self,
+        _adapter: &Self::AdapterId,
+        desc: &crate::DeviceDescriptor,
+        _trace_dir: Option<&std::path::Path>,
+    ) -> Self::RequestDeviceFuture {
+        let error_sink = ErrorSinkRaw::new_sink();
+        let commands = CommandLog::default();
+        let device = Device {
+            features: desc.features,
+            limits: desc.limits.clone(),
+            error_sink: Arc::clone(&error_sink),
+            commands: Arc::clone(&commands),
+        };
+        ready(Ok((
+            device,
+            Queue {
+                error_sink,
+                commands,
+            },
+        )))
+    }
+
+    fn adapter_features(&self, _adapter: &Self::AdapterId) -> Features {
+        Features::all()
+    }
+
+    fn adapter_limits(&self, _adapter: &Self::AdapterId) -> Limits {
+        Limits::default()
+    }
+
+    fn device_features(&self, device: &Self::DeviceId) -> Features {
+        device.features
+    }
+
+    fn device_limits(&self, device: &Self::DeviceId) -> Limits {
+        device.limits.clone()
+    }
+
+    fn device_create_swap_chain(
+        &self,
+        device: &Self::DeviceId,
+        _surface: &Self::SurfaceId,
+        _desc: &wgt::SwapChainDescriptor,
+    ) -> Self::SwapChainId {
+        SwapChain {
+            commands: Arc::clone(&device.commands),
+        }
+    }
+
+    fn device_create_shader_module(
+        &self,
+        _device: &Self::DeviceId,
+        _source: ShaderModuleSource,
+    ) -> Result<Self::ShaderModuleId, CreateError> {
+        Ok(Handle {
+            id: self.register(ResourceKind::ShaderModule),
+        })
+    }
+
+    fn device_create_bind_group_layout(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &BindGroupLayoutDescriptor,
+    ) -> Result<Self::BindGroupLayoutId, CreateError> {
+        Ok(Handle {
+            id: self.register(ResourceKind::BindGroupLayout),
+        })
+    }
+
+    fn device_create_bind_group(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &BindGroupDescriptor,
+    ) -> Result<Self::BindGroupId, CreateError> {
+        Ok(Handle {
+            id: self.register(ResourceKind::BindGroup),
+        })
+    }
+
+    fn device_create_pipeline_layout(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &PipelineLayoutDescriptor,
+    ) -> Result<Self::PipelineLayoutId, CreateError> {
+        Ok(Handle {
+            id: self.register(ResourceKind::PipelineLayout),
+        })
+    }
+
+    fn device_create_render_pipeline(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &RenderPipelineDescriptor,
+    ) -> Result<Self::RenderPipelineId, CreateError> {
+        Ok(Handle {
+            id: self.register(ResourceKind::RenderPipeline),
+        })
+    }
+
+    fn device_create_compute_pipeline(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &ComputePipelineDescriptor,
+    ) -> Result<Self::ComputePipelineId, CreateError> {
+        Ok(Handle {
+            id: self.register(ResourceKind::ComputePipeline),
+        })
+    }
+
+    fn device_create_buffer(
+        &self,
+        _device: &Self::DeviceId,
+        desc: &BufferDescriptor,
+    ) -> Result<Self::BufferId, CreateError> {
+        Ok(Arc::new(Buffer {
+            id: self.register(ResourceKind::Buffer),
+            size: desc.size,
+            usage: desc.usage,
+            data: Mutex::new(vec![0; desc.size as usize]),
+            mapped: AtomicBool::new(desc.mapped_at_creation),
+        }))
+    }
+
+    fn device_create_texture(
+        &self,
+        _device: &Self::DeviceId,
+        desc: &TextureDescriptor,
+    ) -> Result<Self::TextureId, CreateError> {
+        Ok(Arc::new(Texture::new(
+            self.register(ResourceKind::Texture),
+            desc,
+        )))
+    }
+
+    fn device_create_sampler(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &SamplerDescriptor,
+    ) -> Result<Self::SamplerId, CreateError> {
+        Ok(Handle {
+            id: self.register(ResourceKind::Sampler),
+        })
+    }
+
+    fn device_create_command_encoder(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &CommandEncoderDescriptor,
+    ) -> Self::CommandEncoderId {
+        CommandEncoder {
+            operations: Mutex::new(Vec::new()),
+        }
+    }
+
+    fn device_create_render_bundle_encoder(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &wgt::RenderBundleEncoderDescriptor,
+    ) -> Self::RenderBundleEncoderId {
+        CommandRecorder::default()
+    }
+
+    fn device_drop(&self, _device: &Self::DeviceId) {}
+
+    fn device_poll(&self, _device: &Self::DeviceId, _maintain: crate::Maintain) {
+        // All operations complete immediately
+    }
+
+    fn device_push_error_scope(&self, device: &Self::DeviceId, filter: crate::ErrorFilter) {
+        device.error_sink.lock().push_scope(filter);
+    }
+
+    fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture {
+        ready(device.error_sink.lock().pop_scope())
+    }
+
+    fn device_on_uncaptured_error(
+        &self,
+        device: &Self::DeviceId,
+        handler: Box<dyn Fn(crate::Error) + Send + Sync>,
+    ) {
+        device
+            .error_sink
+            .lock()
+            .set_uncaptured_handler(handler.into());
+    }
+
+    fn device_capture_error(&self, device: &Self::DeviceId, error: crate::Error) {
+        let _ = device.error_sink.lock().capture(error);
+    }
+
+    fn device_report_error(&self, device: &Self::DeviceId, error: crate::Error) {
+        error::handle_error(&device.error_sink, error);
+    }
+
+    fn buffer_map_async(
+        &self,
+        buffer: &Self::BufferId,
+        mode: MapMode,
+        _range: Range<BufferAddress>,
+    ) -> Self::MapAsyncFuture {
+        let usage = match mode {
+            MapMode::Read => BufferUsage::MAP_READ,
+            MapMode::Write => BufferUsage::MAP_WRITE,
+        };
+        if buffer.usage.contains(usage) {
+            buffer.mapped.store(true, Ordering::Release);
+            ready(Ok(()))
+        } else {
+            ready(Err(crate::BufferAsyncError))
+        }
+    }
+
+    fn buffer_get_mapped_range(
+        &self,
+        buffer: &Self::BufferId,
+        sub_range: Range<BufferAddress>,
+    ) -> &[u8] {
+        let ptr = buffer.mapped_ptr(&sub_range);
+        unsafe { slice::from_raw_parts(ptr, (sub_range.end - sub_range.start) as usize) }
+    }
+
+    fn buffer_get_mapped_range_mut(
+        &self,
+        buffer: &Self::BufferId,
+        sub_range: Range<BufferAddress>,
+    ) -> &mut [u8] {
+        let ptr = buffer.mapped_ptr(&sub_range);
+        unsafe { slice::from_raw_parts_mut(ptr, (sub_range.end - sub_range.start) as usize) }
+    }
+
+    fn buffer_unmap(&self, buffer: &Self::BufferId) {
+        buffer.mapped.store(false, Ordering::Release);
+    }
+
+    fn swap_chain_get_next_texture(
+        &self,
+        swap_chain: &Self::SwapChainId,
+    ) -> (
+        Option<Self::TextureViewId>,
+        SwapChainStatus,
+        Self::SwapChainOutputDetail,
+    ) {
+        let view = Handle {
+            id: self.register(ResourceKind::TextureView),
+        };
+        (
+            Some(view),
+            SwapChainStatus::Good,
+            Arc::clone(&swap_chain.commands),
+        )
+    }
+
+    fn swap_chain_present(&self, view: &Self::TextureViewId, detail: &Self::SwapChainOutputDetail) {
+        detail.lock().push(Command::Present(view.id));
+        // Swap chain views are not owned, so they go away on presentation.
+        self.unregister(view.id);
+    }
+
+    fn texture_create_view(
+        &self,
+        _texture: &Self::TextureId,
+        _desc: Option<&TextureViewDescriptor>,
+    ) -> Self::TextureViewId {
+        Handle {
+            id: self.register(ResourceKind::TextureView),
+        }
+    }
+
+    fn texture_drop(&self, texture: &Self::TextureId) {
+        self.unregister(texture.id);
+    }
+    fn texture_view_drop(&self, texture_view: &Self::TextureViewId) {
+        self.unregister(texture_view.id);
+    }
+    fn sampler_drop(&self, sampler: &Self::SamplerId) {
+        self.unregister(sampler.id);
+    }
+    fn buffer_drop(&self, buffer: &Self::BufferId) {
+        self.unregister(buffer.id);
+    }
+    fn bind_group_drop(&self, bind_group: &Self::BindGroupId) {
+        self.unregister(bind_group.id);
+    }
+    fn bind_group_layout_drop(&self, bind_group_layout: &Self::BindGroupLayoutId) {
+        self.unregister(bind_group_layout.id);
+    }
+    fn pipeline_layout_drop(&self, pipeline_layout: &Self::PipelineLayoutId) {
+        self.unregister(pipeline_layout.id);
+    }
+    fn shader_module_drop(&self, shader_module: &Self::ShaderModuleId) {
+        self.unregister(shader_module.id);
+    }
+    fn command_buffer_drop(&self, _command_buffer: &Self::CommandBufferId) {}
+    fn render_bundle_drop(&self, render_bundle: &Self::RenderBundleId) {
+        self.unregister(render_bundle.id);
+    }
+    fn compute_pipeline_drop(&self, pipeline: &Self::ComputePipelineId) {
+        self.unregister(pipeline.id);
+    }
+    fn render_pipeline_drop(&self, pipeline: &Self::RenderPipelineId) {
+        self.unregister(pipeline.id);
+    }
+
+    fn command_encoder_copy_buffer_to_buffer(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        source: &Self::BufferId,
+        source_offset: BufferAddress,
+        destination: &Self::BufferId,
+        destination_offset: BufferAddress,
+        copy_size: BufferAddress,
+    ) {
+        encoder
+            .operations
+            .lock()
+            .push(Operation::CopyBufferToBuffer {
+                source: Arc::clone(source),
+                source_offset,
+                destination: Arc::clone(destination),
+                destination_offset,
+                size: copy_size,
+            });
+    }
+
+    fn command_encoder_copy_buffer_to_texture(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        source: crate::BufferCopyView,
+        destination: crate::TextureCopyView,
+        copy_size: wgt::Extent3d,
+    ) {
+        encoder
+            .operations
+            .lock()
+            .push(Operation::CopyBufferToTexture {
+                source: BufferRegion::new(source),
+                destination: TextureRegion::new(destination),
+                size: copy_size,
+            });
+    }
+
+    fn command_encoder_copy_texture_to_buffer(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        source: crate::TextureCopyView,
+        destination: crate::BufferCopyView,
+        copy_size: wgt::Extent3d,
+    ) {
+        encoder
+            .operations
+            .lock()
+            .push(Operation::CopyTextureToBuffer {
+                source: TextureRegion::new(source),
+                destination: BufferRegion::new(destination),
+                size: copy_size,
+            });
+    }
+
+    fn command_encoder_copy_texture_to_texture(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        source: crate::TextureCopyView,
+        destination: crate::TextureCopyView,
+        copy_size: wgt::Extent3d,
+    ) {
+        encoder
+            .operations
+            .lock()
+            .push(Operation::CopyTextureToTexture {
+                source: TextureRegion::new(source),
+                destination: TextureRegion::new(destination),
+                size: copy_size,
+            });
+    }
+
+    fn command_encoder_begin_compute_pass(
+        &self,
+        _encoder: &Self::CommandEncoderId,
+    ) -> Self::ComputePassId {
+        CommandRecorder {
+            commands: vec![Command::BeginComputePass],
+        }
+    }
+
+    fn command_encoder_end_compute_pass(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        pass: &mut Self::ComputePassId,
+    ) {
+        pass.commands.push(Command::EndComputePass);
+        encoder
+            .operations
+            .lock()
+            .extend(pass.commands.drain(..).map(Operation::Record));
+    }
+
+    fn command_encoder_begin_render_pass<'a>(
+        &self,
+        _encoder: &Self::CommandEncoderId,
+        desc: &crate::RenderPassDescriptor<'a, '_>,
+    ) -> Self::RenderPassId {
+        CommandRecorder {
+            commands: vec![Command::BeginRenderPass {
+                color_attachments: desc
+                    .color_attachments
+                    .iter()
+                    .map(|ca| ca.attachment.id.mock().id)
+                    .collect(),
+                depth_stencil_attachment: desc
+                    .depth_stencil_attachment
+                    .as_ref()
+                    .map(|dsa| dsa.attachment.id.mock().id),
+            }],
+        }
+    }
+
+    fn command_encoder_end_render_pass(
+        &self,
+        encoder: &Self::CommandEncoderId,
+        pass: &mut Self::RenderPassId,
+    ) {
+        pass.commands.push(Command::EndRenderPass);
+        encoder
+            .operations
+            .lock()
+            .extend(pass.commands.drain(..).map(Operation::Record));
+    }
+
+    fn command_encoder_finish(&self, encoder: &Self::CommandEncoderId) -> Self::CommandBufferId {
+        CommandBuffer {
+            operations: encoder.operations.lock().drain(..).collect(),
+        }
+    }
+
+    fn render_bundle_encoder_finish(
+        &self,
+        encoder: Self::RenderBundleEncoderId,
+        _desc: &crate::RenderBundleDescriptor,
+    ) -> Self::RenderBundleId {
+        RenderBundle {
+            id: self.register(ResourceKind::RenderBundle),
+            commands: encoder.commands,
+        }
+    }
+
+    fn queue_write_buffer(
+        &self,
+        queue: &Self::QueueId,
+        buffer: &Self::BufferId,
+        offset: BufferAddress,
+        data: &[u8],
+    ) {
+        let size = data.len() as BufferAddress;
+        if let Err(description) = buffer.validate_range(offset, size, BufferUsage::COPY_DST) {
+            handle_error(&queue.error_sink, description);
+            return;
+        }
+        buffer.data.lock()[offset as usize..(offset + size) as usize].copy_from_slice(data);
+        queue.commands.lock().push(Command::WriteBuffer {
+            buffer: buffer.id,
+            offset,
+            size,
+        });
+    }
+
+    fn queue_write_texture(
+        &self,
+        queue: &Self::QueueId,
+        texture: crate::TextureCopyView,
+        data: &[u8],
+        data_layout: wgt::TextureDataLayout,
+        size: wgt::Extent3d,
+    ) {
+        let destination = TextureRegion::new(texture);
+        let row_size = destination.row_size(size);
+        let result = validate_linear(&data_layout, size, row_size, data.len() as BufferAddress, 1)
+            .and_then(|()| destination.validate(size, wgt::TextureUsage::COPY_DST));
+        if let Err(description) = result {
+            handle_error(&queue.error_sink, description);
+            return;
+        }
+        let texels = linear_rows(&data_layout, size, row_size)
+            .flat_map(|range| data[range].iter().cloned())
+            .collect::<Vec<_>>();
+        destination.write(size, &texels);
+        queue.commands.lock().push(Command::WriteTexture {
+            texture: destination.texture.id,
+            size,
+        });
+    }
+
+    fn queue_submit<I: Iterator<Item = Self::CommandBufferId>>(
+        &self,
+        queue: &Self::QueueId,
+        command_buffers: I,
+    ) {
+        for command_buffer in command_buffers {
+            for operation in command_buffer.operations {
+                let result = match operation {
+                    Operation::Record(command) => Ok(command),
+                    Operation::CopyBufferToBuffer {
+                        source,
+                        source_offset,
+                        destination,
+                        destination_offset,
+                        size,
+                    } => copy_buffer_to_buffer(
+                        &source,
+                        source_offset,
+                        &destination,
+                        destination_offset,
+                        size,
+                    )
+                    .map(|()| Command::CopyBufferToBuffer {
+                        source: source.id,
+                        source_offset,
+                        destination: destination.id,
+                        destination_offset,
+                        size,
+                    }),
+                    Operation::CopyBufferToTexture {
+                        source,
+                        destination,
+                        size,
+                    } => copy_buffer_to_texture(&source, &destination, size).map(|()| {
+                        Command::CopyBufferToTexture {
+                            source: source.buffer.id,
+                            destination: destination.texture.id,
+                            size,
+                        }
+                    }),
+                    Operation::CopyTextureToBuffer {
+                        source,
+                        destination,
+                        size,
+                    } => copy_texture_to_buffer(&source, &destination, size).map(|()| {
+                        Command::CopyTextureToBuffer {
+                            source: source.texture.id,
+                            destination: destination.buffer.id,
+                            size,
+                        }
+                    }),
+                    Operation::CopyTextureToTexture {
+                        source,
+                        destination,
+                        size,
+                    } => copy_texture_to_texture(&source, &destination, size).map(|()| {
+                        Command::CopyTextureToTexture {
+                            source: source.texture.id,
+                            destination: destination.texture.id,
+                            size,
+                        }
+                    }),
+                };
+                match result {
+                    Ok(command) => queue.commands.lock().push(command),
+                    Err(description) => handle_error(&queue.error_sink, description),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::{request_device, Command, Resource};
+
+    #[test]
+    fn copy_buffer_to_buffer() {
+        use crate::{BufferDescriptor, BufferUsage, CommandEncoderDescriptor, MapMode};
+
+        let (device, queue) = request_device();
+        let source =
+            device.create_buffer_with_data(&[1, 2, 3, 4, 5, 6, 7, 8], BufferUsage::COPY_SRC);
+        let destination = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: 8,
+            usage: BufferUsage::COPY_DST | BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+        queue.write_buffer(&destination, 0, &[9, 9, 9, 9]);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&source, 4, &destination, 4, 4);
+        queue.submit(Some(encoder.finish()));
+
+        let slice = destination.slice(..);
+        futures::executor::block_on(slice.map_async(MapMode::Read)).unwrap();
+        assert_eq!(&*slice.get_mapped_range(), &[9, 9, 9, 9, 5, 6, 7, 8]);
+        assert_eq!(
+            queue.command_log(),
+            vec![
+                Command::WriteBuffer {
+                    buffer: destination.mock_id(),
+                    offset: 0,
+                    size: 4,
+                },
+                Command::CopyBufferToBuffer {
+                    source: source.mock_id(),
+                    source_offset: 4,
+                    destination: destination.mock_id(),
+                    destination_offset: 4,
+                    size: 4,
+                },
+            ]
+        );
+        assert_eq!(
+            device.live_resources(),
+            vec![source.mock_id(), destination.mock_id()]
+        );
+    }
+
+    #[test]
+    fn texture_round_trip() {
+        use crate::{
+            BufferCopyView, BufferDescriptor, BufferUsage, CommandEncoderDescriptor, Extent3d,
+            MapMode, Origin3d, TextureCopyView, TextureDataLayout, TextureDescriptor,
+            TextureDimension, TextureFormat, TextureUsage, COPY_BYTES_PER_ROW_ALIGNMENT,
+        };
+
+        let (device, queue) = request_device();
+        let texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: 4,
+                height: 4,
+                depth: 2,
+            },
+            mip_level_count: 2,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::R8Uint,
+            usage: TextureUsage::COPY_SRC | TextureUsage::COPY_DST,
+        });
+        let size = Extent3d {
+            width: 2,
+            height: 2,
+            depth: 1,
+        };
+        // Second layer of the second mip level, with one byte of padding per row.
+        queue.write_texture(
+            TextureCopyView {
+                texture: &texture,
+                mip_level: 1,
+                origin: Origin3d { x: 0, y: 0, z: 1 },
+            },
+            &[1, 2, 0, 3, 4, 0],
+            TextureDataLayout {
+                offset: 0,
+                bytes_per_row: 3,
+                rows_per_image: 0,
+            },
+            size,
+        );
+
+        let bytes_per_row = COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: 2 * bytes_per_row as u64,
+            usage: BufferUsage::COPY_DST | BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            TextureCopyView {
+                texture: &texture,
+                mip_level: 1,
+                origin: Origin3d { x: 0, y: 0, z: 1 },
+            },
+            BufferCopyView {
+                buffer: &buffer,
+                layout: TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row,
+                    rows_per_image: 0,
+                },
+            },
+            size,
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        futures::executor::block_on(slice.map_async(MapMode::Read)).unwrap();
+        let data = slice.get_mapped_range();
+        assert_eq!(&data[..2], &[1, 2]);
+        assert_eq!(&data[bytes_per_row as usize..][..2], &[3, 4]);
+    }
+
+    #[test]
+    fn copy_uncopyable_texture() {
+        use crate::{
+            BufferCopyView, BufferDescriptor, BufferUsage, CommandEncoderDescriptor, Error,
+            ErrorFilter, Extent3d, Origin3d, TextureCopyView, TextureDataLayout, TextureDescriptor,
+            TextureDimension, TextureFormat, TextureUsage, COPY_BYTES_PER_ROW_ALIGNMENT,
+        };
+
+        let (device, queue) = request_device();
+        let size = Extent3d {
+            width: 1,
+            height: 1,
+            depth: 1,
+        };
+        let texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Depth24PlusStencil8,
+            usage: TextureUsage::COPY_SRC,
+        });
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: COPY_BYTES_PER_ROW_ALIGNMENT as u64,
+            usage: BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        device.push_error_scope(ErrorFilter::Validation);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            TextureCopyView {
+                texture: &texture,
+                mip_level: 0,
+                origin: Origin3d::ZERO,
+            },
+            BufferCopyView {
+                buffer: &buffer,
+                layout: TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: COPY_BYTES_PER_ROW_ALIGNMENT,
+                    rows_per_image: 1,
+                },
+            },
+            size,
+        );
+        queue.submit(Some(encoder.finish()));
+        let captured = futures::executor::block_on(device.pop_error_scope());
+        assert!(matches!(captured, Some(Error::Validation { .. })));
+    }
+}
@@ -1,13 +1,42 @@
 #[cfg(target_arch = "wasm32")]
 mod web;
-#[cfg(target_arch = "wasm32")]
+#[cfg(all(target_arch = "wasm32", not(feature = "mock")))]
 pub(crate) use web::Context;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod direct;
 
-#[cfg(not(target_arch = "wasm32"))]
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "mock")))]
 pub(crate) use direct::Context;
 
 #[cfg(not(target_arch = "wasm32"))]
 mod native_gpu_future;
+
+#[cfg(feature = "mock")]
+mod mock;
+
+#[cfg(feature = "mock")]
+mod dispatch;
+
+#[cfg(feature = "mock")]
+pub(crate) use dispatch::Context;
+
+#[cfg(feature = "mock")]
+pub(crate) use dispatch::real;
+
+#[cfg(all(not(target_arch = "wasm32"), feature = "mock"))]
+pub(crate) use dispatch::from_real;
+
+mod error;
+
+/// Returns the object of the real backend inside `object`.
+#[cfg(not(feature = "mock"))]
+pub(crate) fn real<T>(object: &T) -> &T {
+    object
+}
+
+/// Wraps an object of the real backend.
+#[cfg(all(not(target_arch = "wasm32"), not(feature = "mock")))]
+pub(crate) fn from_real<T>(object: T) -> T {
+    object
+}
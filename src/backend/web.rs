@@ -1,19 +1,27 @@
 use crate::{
+    backend::{
+        error::{self, ErrorSink, ErrorSinkRaw},
+        real,
+    },
     BindGroupDescriptor, BindGroupLayoutDescriptor, BindingResource, BindingType, BufferDescriptor,
-    CommandEncoderDescriptor, ComputePipelineDescriptor, LoadOp, PipelineLayoutDescriptor,
-    ProgrammableStageDescriptor, RenderPipelineDescriptor, SamplerDescriptor, ShaderModuleSource,
-    SwapChainStatus, TextureDescriptor, TextureViewDescriptor, TextureViewDimension,
+    CommandEncoderDescriptor, ComputePipelineDescriptor, CreateError, LoadOp,
+    PipelineLayoutDescriptor, ProgrammableStageDescriptor, RenderPipelineDescriptor,
+    SamplerDescriptor, ShaderModuleSource, SwapChainStatus, TextureDescriptor,
+    TextureViewDescriptor, TextureViewDimension,
 };
 
 use futures::FutureExt;
 use std::{
+    cell::RefCell,
+    fmt,
     future::Future,
-    marker::PhantomData,
     ops::Range,
     pin::Pin,
+    slice,
+    sync::Arc,
     task::{self, Poll},
 };
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::{prelude::*, JsCast};
 
 // We need to make a wrapper for some of the handle types returned by the web backend to make them
 // implement `Send` and `Sync` to match native.
@@ -30,8 +38,98 @@ unsafe impl<T> Sync for Sendable<T> {}
 
 pub(crate) type Context = Sendable<web_sys::Gpu>;
 
+#[derive(Debug)]
+pub(crate) struct Buffer {
+    buffer: web_sys::GpuBuffer,
+    mapping: RefCell<Option<BufferMapping>>,
+}
+
+pub(crate) struct Device {
+    device: web_sys::GpuDevice,
+    /// Shadow of the browser's error scopes, capturing the errors of checks done by this
+    /// crate, and uncaptured error handler for them.
+    error_sink: ErrorSink,
+    /// Callback set as `onuncapturederror`, kept alive until it is replaced.
+    uncaptured_handler: RefCell<Option<Closure<dyn FnMut(web_sys::GpuUncapturedErrorEvent)>>>,
+}
+
+impl fmt::Debug for Device {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Device")
+            .field("device", &self.device)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+struct BufferMapping {
+    range: Range<wgt::BufferAddress>,
+    writable: bool,
+    /// The mapped `ArrayBuffer` and a CPU-side copy of it, created on first access.
+    shadow: Option<(js_sys::ArrayBuffer, Vec<u8>)>,
+}
+
+impl Buffer {
+    /// Returns a pointer to the start of `sub_range` of the mapped buffer.
+    fn mapped_ptr(&self, sub_range: &Range<wgt::BufferAddress>) -> *mut u8 {
+        let mut mapping = self.mapping.borrow_mut();
+        let mapping = mapping.as_mut().expect("Buffer is not mapped");
+        let range = mapping.range.clone();
+        assert!(range.start <= sub_range.start && sub_range.end <= range.end);
+        let buffer = &self.buffer;
+        let (_, shadow) = mapping.shadow.get_or_insert_with(|| {
+            let array_buffer = buffer
+                .unchecked_ref::<GpuBufferExt>()
+                .get_mapped_range(range.start as f64, (range.end - range.start) as f64);
+            let shadow = js_sys::Uint8Array::new(&array_buffer).to_vec();
+            (array_buffer, shadow)
+        });
+        // The shadow copy is not reallocated until the buffer is unmapped, and unmapping
+        // requires all mapped views to be dropped first. `Vec::as_mut_ptr` doesn't create
+        // a reference to the whole copy, so views of the ranges handed out by `MapContext`
+        // don't invalidate each other.
+        unsafe {
+            shadow
+                .as_mut_ptr()
+                .add((sub_range.start - range.start) as usize)
+        }
+    }
+}
+
+const GPU_MAP_MODE_READ: u32 = 1;
+const GPU_MAP_MODE_WRITE: u32 = 2;
+
+// Parts of the WebGPU API that are not exposed by web-sys yet.
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = GPUBuffer)]
+    type GpuBufferExt;
+    #[wasm_bindgen(method, js_name = mapAsync)]
+    fn map_async(this: &GpuBufferExt, mode: u32, offset: f64, size: f64) -> js_sys::Promise;
+    #[wasm_bindgen(method, js_name = getMappedRange)]
+    fn get_mapped_range(this: &GpuBufferExt, offset: f64, size: f64) -> js_sys::ArrayBuffer;
+
+    #[wasm_bindgen(js_name = GPUQueue)]
+    type GpuQueueExt;
+    #[wasm_bindgen(method, js_name = writeBuffer)]
+    fn write_buffer(this: &GpuQueueExt, buffer: &web_sys::GpuBuffer, offset: f64, data: &[u8]);
+    #[wasm_bindgen(method, js_name = writeTexture)]
+    fn write_texture(
+        this: &GpuQueueExt,
+        destination: &web_sys::GpuTextureCopyView,
+        data: &[u8],
+        data_layout: &js_sys::Object,
+        size: &web_sys::GpuExtent3dDict,
+    );
+}
+
+fn set_property(object: &JsValue, key: &str, value: &JsValue) {
+    js_sys::Reflect::set(object, &key.into(), value).expect("Failed to set a property");
+}
+
 pub(crate) struct ComputePass(web_sys::GpuComputePassEncoder);
 pub(crate) struct RenderPass(web_sys::GpuRenderPassEncoder);
+pub(crate) struct RenderBundleEncoder(web_sys::GpuRenderBundleEncoder);
 
 // We need to assert that any future we return is Send to match the native API.
 //
@@ -52,6 +150,25 @@ impl<F: Future> Future for MakeSendFuture<F> {
 
 unsafe impl<F> Send for MakeSendFuture<F> {}
 
+/// Pops an error scope of the browser, preferring the error its shadow scope captured.
+pub(crate) struct PopErrorScope {
+    /// Error of a check done by this crate, which the browser's scope doesn't know about.
+    shadow: Option<crate::Error>,
+    browser: FutureMap<Option<crate::Error>>,
+}
+
+impl Future for PopErrorScope {
+    type Output = Option<crate::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let shadow = &mut this.shadow;
+        this.browser
+            .poll_unpin(cx)
+            .map(|error| shadow.take().or(error))
+    }
+}
+
 impl crate::ComputePassInner<Context> for ComputePass {
     fn set_pipeline(&mut self, pipeline: &Sendable<web_sys::GpuComputePipeline>) {
         self.0.set_pipeline(&pipeline.0);
@@ -79,137 +196,106 @@ impl crate::ComputePassInner<Context> for ComputePass {
     }
     fn dispatch_indirect(
         &mut self,
-        indirect_buffer: &Sendable<web_sys::GpuBuffer>,
+        indirect_buffer: &Sendable<Buffer>,
         indirect_offset: wgt::BufferAddress,
     ) {
         self.0
-            .dispatch_indirect_with_f64(&indirect_buffer.0, indirect_offset as f64);
+            .dispatch_indirect_with_f64(&indirect_buffer.0.buffer, indirect_offset as f64);
     }
 }
 
-impl crate::RenderInner<Context> for RenderPass {
-    fn set_pipeline(&mut self, pipeline: &Sendable<web_sys::GpuRenderPipeline>) {
-        self.0.set_pipeline(&pipeline.0);
-    }
-    fn set_bind_group(
-        &mut self,
-        index: u32,
-        bind_group: &Sendable<web_sys::GpuBindGroup>,
-        offsets: &[wgt::DynamicOffset],
-    ) {
-        self.0
-            .set_bind_group_with_u32_array_and_f64_and_dynamic_offsets_data_length(
-                index,
-                &bind_group.0,
-                offsets,
-                0f64,
-                offsets.len() as u32,
-            );
-    }
-    fn set_index_buffer(
-        &mut self,
-        buffer: &Sendable<web_sys::GpuBuffer>,
-        offset: wgt::BufferAddress,
-        size: Option<wgt::BufferSize>,
-    ) {
-        self.0.set_index_buffer_with_f64_and_f64(
-            &buffer.0,
-            offset as f64,
-            size.expect("TODO").get() as f64,
-        );
-    }
-    fn set_vertex_buffer(
-        &mut self,
-        slot: u32,
-        buffer: &Sendable<web_sys::GpuBuffer>,
-        offset: wgt::BufferAddress,
-        size: Option<wgt::BufferSize>,
-    ) {
-        self.0.set_vertex_buffer_with_f64_and_f64(
-            slot,
-            &buffer.0,
-            offset as f64,
-            size.expect("TODO").get() as f64,
-        );
-    }
-    fn set_push_constants(&mut self, _stages: wgt::ShaderStage, _offset: u32, _data: &[u32]) {
-        panic!("PUSH_CONSTANTS feature must be enabled to call multi_draw_indexed_indirect")
-    }
-    fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
-        self.0
-            .draw_with_instance_count_and_first_vertex_and_first_instance(
-                vertices.end - vertices.start,
-                instances.end - instances.start,
-                vertices.start,
-                instances.start,
-            );
-    }
-    fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
-        self.0
-            .draw_indexed_with_instance_count_and_first_index_and_base_vertex_and_first_instance(
-                indices.end - indices.start,
-                instances.end - instances.start,
-                indices.start,
-                base_vertex,
-                instances.start,
-            );
-    }
-    fn draw_indirect(
-        &mut self,
-        indirect_buffer: &Sendable<web_sys::GpuBuffer>,
-        indirect_offset: wgt::BufferAddress,
-    ) {
-        self.0
-            .draw_indirect_with_f64(&indirect_buffer.0, indirect_offset as f64);
-    }
-    fn draw_indexed_indirect(
-        &mut self,
-        indirect_buffer: &Sendable<web_sys::GpuBuffer>,
-        indirect_offset: wgt::BufferAddress,
-    ) {
-        self.0
-            .draw_indexed_indirect_with_f64(&indirect_buffer.0, indirect_offset as f64);
-    }
-    fn multi_draw_indirect(
-        &mut self,
-        _indirect_buffer: &Sendable<web_sys::GpuBuffer>,
-        _indirect_offset: wgt::BufferAddress,
-        _count: u32,
-    ) {
-        panic!("MULTI_DRAW_INDIRECT feature must be enabled to call multi_draw_indirect")
-    }
-    fn multi_draw_indexed_indirect(
-        &mut self,
-        _indirect_buffer: &Sendable<web_sys::GpuBuffer>,
-        _indirect_offset: wgt::BufferAddress,
-        _count: u32,
-    ) {
-        panic!("MULTI_DRAW_INDIRECT feature must be enabled to call multi_draw_indexed_indirect")
-    }
-    fn multi_draw_indirect_count(
-        &mut self,
-        _indirect_buffer: &Sendable<web_sys::GpuBuffer>,
-        _indirect_offset: wgt::BufferAddress,
-        _count_buffer: &Sendable<web_sys::GpuBuffer>,
-        _count_buffer_offset: wgt::BufferAddress,
-        _max_count: u32,
-    ) {
-        panic!(
-            "MULTI_DRAW_INDIRECT_COUNT feature must be enabled to call multi_draw_indirect_count"
-        )
-    }
-    fn multi_draw_indexed_indirect_count(
-        &mut self,
-        _indirect_buffer: &Sendable<web_sys::GpuBuffer>,
-        _indirect_offset: wgt::BufferAddress,
-        _count_buffer: &Sendable<web_sys::GpuBuffer>,
-        _count_buffer_offset: wgt::BufferAddress,
-        _max_count: u32,
-    ) {
-        panic!("MULTI_DRAW_INDIRECT_COUNT feature must be enabled to call multi_draw_indexed_indirect_count")
-    }
+// `GPURenderPassEncoder` and `GPURenderBundleEncoder` share the `GPURenderEncoderBase` methods.
+macro_rules! impl_render_inner {
+    ($($name:ident),*) => {$(
+        impl crate::RenderInner<Context> for $name {
+            fn set_pipeline(&mut self, pipeline: &Sendable<web_sys::GpuRenderPipeline>) {
+                self.0.set_pipeline(&pipeline.0);
+            }
+            fn set_bind_group(
+                &mut self,
+                index: u32,
+                bind_group: &Sendable<web_sys::GpuBindGroup>,
+                offsets: &[wgt::DynamicOffset],
+            ) {
+                self.0
+                    .set_bind_group_with_u32_array_and_f64_and_dynamic_offsets_data_length(
+                        index,
+                        &bind_group.0,
+                        offsets,
+                        0f64,
+                        offsets.len() as u32,
+                    );
+            }
+            fn set_index_buffer(
+                &mut self,
+                buffer: &Sendable<Buffer>,
+                offset: wgt::BufferAddress,
+                size: Option<wgt::BufferSize>,
+            ) {
+                self.0.set_index_buffer_with_f64_and_f64(
+                    &buffer.0.buffer,
+                    offset as f64,
+                    size.expect("TODO").get() as f64,
+                );
+            }
+            fn set_vertex_buffer(
+                &mut self,
+                slot: u32,
+                buffer: &Sendable<Buffer>,
+                offset: wgt::BufferAddress,
+                size: Option<wgt::BufferSize>,
+            ) {
+                self.0.set_vertex_buffer_with_f64_and_f64(
+                    slot,
+                    &buffer.0.buffer,
+                    offset as f64,
+                    size.expect("TODO").get() as f64,
+                );
+            }
+            fn set_push_constants(&mut self, _stages: wgt::ShaderStage, _offset: u32, _data: &[u32]) {
+                panic!("PUSH_CONSTANTS feature must be enabled to call multi_draw_indexed_indirect")
+            }
+            fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+                self.0
+                    .draw_with_instance_count_and_first_vertex_and_first_instance(
+                        vertices.end - vertices.start,
+                        instances.end - instances.start,
+                        vertices.start,
+                        instances.start,
+                    );
+            }
+            fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
+                self.0
+                    .draw_indexed_with_instance_count_and_first_index_and_base_vertex_and_first_instance(
+                        indices.end - indices.start,
+                        instances.end - instances.start,
+                        indices.start,
+                        base_vertex,
+                        instances.start,
+                    );
+            }
+            fn draw_indirect(
+                &mut self,
+                indirect_buffer: &Sendable<Buffer>,
+                indirect_offset: wgt::BufferAddress,
+            ) {
+                self.0
+                    .draw_indirect_with_f64(&indirect_buffer.0.buffer, indirect_offset as f64);
+            }
+            fn draw_indexed_indirect(
+                &mut self,
+                indirect_buffer: &Sendable<Buffer>,
+                indirect_offset: wgt::BufferAddress,
+            ) {
+                self.0
+                    .draw_indexed_indirect_with_f64(&indirect_buffer.0.buffer, indirect_offset as f64);
+            }
+        }
+    )*};
 }
 
+impl_render_inner!(RenderPass, RenderBundleEncoder);
+
 impl crate::RenderPassInner<Context> for RenderPass {
     fn set_blend_color(&mut self, color: wgt::Color) {
         self.0
@@ -234,20 +320,46 @@ impl crate::RenderPassInner<Context> for RenderPass {
         self.0.set_stencil_reference(reference);
     }
 
-    fn insert_debug_marker(&mut self, _label: &str) {
-        unimplemented!()
+    fn insert_debug_marker(&mut self, label: &str) {
+        self.0.insert_debug_marker(label);
     }
 
-    fn push_debug_group(&mut self, _group_label: &str) {
-        unimplemented!()
+    fn push_debug_group(&mut self, group_label: &str) {
+        self.0.push_debug_group(group_label);
     }
 
     fn pop_debug_group(&mut self) {
-        unimplemented!()
+        self.0.pop_debug_group();
     }
 
-    fn execute_bundles<'a, I: Iterator<Item = &'a ()>>(&mut self, _render_bundles: I) {
-        unimplemented!()
+    fn execute_bundles<'a, I: Iterator<Item = &'a Sendable<web_sys::GpuRenderBundle>>>(
+        &mut self,
+        render_bundles: I,
+    ) {
+        let mapped = render_bundles
+            .map(|bundle| &bundle.0)
+            .collect::<js_sys::Array>();
+        self.0.execute_bundles(&mapped);
+    }
+    fn multi_draw_indirect_count(
+        &mut self,
+        _indirect_buffer: &Sendable<Buffer>,
+        _indirect_offset: wgt::BufferAddress,
+        _count_buffer: &Sendable<Buffer>,
+        _count_buffer_offset: wgt::BufferAddress,
+        _max_count: u32,
+    ) {
+        panic!("RenderPass::multi_draw_indirect_count is not supported on the web backend")
+    }
+    fn multi_draw_indexed_indirect_count(
+        &mut self,
+        _indirect_buffer: &Sendable<Buffer>,
+        _indirect_offset: wgt::BufferAddress,
+        _count_buffer: &Sendable<Buffer>,
+        _count_buffer_offset: wgt::BufferAddress,
+        _max_count: u32,
+    ) {
+        panic!("RenderPass::multi_draw_indexed_indirect_count is not supported on the web backend")
     }
 }
 
@@ -309,7 +421,7 @@ fn map_texture_component_type(
 fn map_stage_descriptor(
     desc: &ProgrammableStageDescriptor,
 ) -> web_sys::GpuProgrammableStageDescriptor {
-    web_sys::GpuProgrammableStageDescriptor::new(desc.entry_point, &desc.module.id.0)
+    web_sys::GpuProgrammableStageDescriptor::new(desc.entry_point, &real(&desc.module.id).0)
 }
 
 fn map_cull_mode(cull_mode: wgt::CullMode) -> web_sys::GpuCullMode {
@@ -567,14 +679,24 @@ fn map_texture_view_dimension(
 }
 
 fn map_buffer_copy_view(view: crate::BufferCopyView) -> web_sys::GpuBufferCopyView {
-    let mut mapped = web_sys::GpuBufferCopyView::new(&view.buffer.id.0, view.layout.bytes_per_row);
+    let mut mapped =
+        web_sys::GpuBufferCopyView::new(&real(&view.buffer.id).0.buffer, view.layout.bytes_per_row);
     mapped.rows_per_image(view.layout.rows_per_image);
     mapped.offset(view.layout.offset as f64);
     mapped
 }
 
+fn map_texture_data_layout(layout: wgt::TextureDataLayout) -> js_sys::Object {
+    // `GPUTextureDataLayout` is not exposed by web-sys yet.
+    let mapped = js_sys::Object::new();
+    set_property(&mapped, "offset", &(layout.offset as f64).into());
+    set_property(&mapped, "bytesPerRow", &layout.bytes_per_row.into());
+    set_property(&mapped, "rowsPerImage", &layout.rows_per_image.into());
+    mapped
+}
+
 fn map_texture_copy_view(view: crate::TextureCopyView) -> web_sys::GpuTextureCopyView {
-    let mut mapped = web_sys::GpuTextureCopyView::new(&view.texture.id.0);
+    let mut mapped = web_sys::GpuTextureCopyView::new(&real(&view.texture.id).0);
     mapped.mip_level(view.mip_level);
     mapped.origin(&map_origin_3d(view.origin));
     mapped
@@ -615,9 +737,48 @@ fn map_store_op(store: bool) -> web_sys::GpuStoreOp {
     }
 }
 
+fn map_error_filter(filter: crate::ErrorFilter) -> web_sys::GpuErrorFilter {
+    match filter {
+        crate::ErrorFilter::OutOfMemory => web_sys::GpuErrorFilter::OutOfMemory,
+        crate::ErrorFilter::Validation => web_sys::GpuErrorFilter::Validation,
+    }
+}
+
+fn map_error(js_error: &wasm_bindgen::JsValue) -> crate::Error {
+    if js_error.is_instance_of::<web_sys::GpuOutOfMemoryError>() {
+        return crate::Error::OutOfMemory;
+    }
+    let description = match js_error.dyn_ref::<web_sys::GpuValidationError>() {
+        Some(error) => error.message(),
+        None => js_sys::Reflect::get(js_error, &JsValue::from("message"))
+            .ok()
+            .and_then(|message| message.as_string())
+            .unwrap_or_else(|| format!("{:?}", js_error)),
+    };
+    crate::Error::Validation { description }
+}
+
 type JsFutureResult = Result<wasm_bindgen::JsValue, wasm_bindgen::JsValue>;
 type FutureMap<T> = futures::future::Map<wasm_bindgen_futures::JsFuture, fn(JsFutureResult) -> T>;
 
+/// Reads the limits of a `GPUAdapter` or `GPUDevice`, keeping the defaults for missing entries.
+fn map_limits(object: &JsValue) -> wgt::Limits {
+    let limits =
+        js_sys::Reflect::get(object, &JsValue::from("limits")).unwrap_or(JsValue::UNDEFINED);
+    let get = |key: &str, default: u32| {
+        js_sys::Reflect::get(&limits, &JsValue::from(key))
+            .ok()
+            .and_then(|value| value.as_f64())
+            .map_or(default, |value| value as u32)
+    };
+    // `max_bind_groups` is the only limit the pinned `wgt::Limits` shares with `GPULimits`.
+    let default = wgt::Limits::default();
+    wgt::Limits {
+        max_bind_groups: get("maxBindGroups", default.max_bind_groups),
+        ..default
+    }
+}
+
 fn future_request_adapter(result: JsFutureResult) -> Option<Sendable<web_sys::GpuAdapter>> {
     match result {
         Ok(js_value) => Some(Sendable(web_sys::GpuAdapter::from(js_value))),
@@ -626,58 +787,43 @@ fn future_request_adapter(result: JsFutureResult) -> Option<Sendable<web_sys::Gp
 }
 fn future_request_device(
     result: JsFutureResult,
-) -> Result<(Sendable<web_sys::GpuDevice>, Sendable<web_sys::GpuQueue>), crate::RequestDeviceError>
-{
+) -> Result<(Sendable<Device>, Sendable<web_sys::GpuQueue>), crate::RequestDeviceError> {
     result
         .map(|js_value| {
-            let device_id = web_sys::GpuDevice::from(js_value);
-            let queue_id = device_id.default_queue();
+            let device = web_sys::GpuDevice::from(js_value);
+            let queue_id = device.default_queue();
+            let device_id = Device {
+                device,
+                error_sink: ErrorSinkRaw::new_sink(),
+                uncaptured_handler: RefCell::new(None),
+            };
             (Sendable(device_id), Sendable(queue_id))
         })
         .map_err(|_| crate::RequestDeviceError)
 }
 
-pub(crate) struct MapFuture<T> {
-    child: wasm_bindgen_futures::JsFuture,
-    buffer: Option<web_sys::GpuBuffer>,
-    marker: PhantomData<T>,
-}
-impl<T> Unpin for MapFuture<T> {}
-//type MapData = (web_sys::GpuBuffer, Vec<u8>);
-
-impl std::future::Future for MapFuture<()> {
-    type Output = Result<(), crate::BufferAsyncError>;
-    fn poll(
-        mut self: std::pin::Pin<&mut Self>,
-        context: &mut std::task::Context,
-    ) -> std::task::Poll<Self::Output> {
-        std::future::Future::poll(
-            std::pin::Pin::new(&mut self.as_mut().get_mut().child),
-            context,
-        )
-        .map(|result| {
-            let _buffer = self.buffer.take().unwrap();
-            result
-                .map(|js_value| {
-                    let array_buffer = js_sys::ArrayBuffer::from(js_value);
-                    let _view = js_sys::Uint8Array::new(&array_buffer);
-                    () //TODO
-                })
-                .map_err(|_| crate::BufferAsyncError)
-        })
+fn future_pop_error_scope(result: JsFutureResult) -> Option<crate::Error> {
+    match result {
+        Ok(js_value) if js_value.is_null() => None,
+        Ok(js_value) => Some(map_error(&js_value)),
+        Err(_) => panic!("Called pop_error_scope without a matching push_error_scope"),
     }
 }
 
+fn future_map_async(result: JsFutureResult) -> Result<(), crate::BufferAsyncError> {
+    result.map(|_| ()).map_err(|_| crate::BufferAsyncError)
+}
+
 impl crate::Context for Context {
     type AdapterId = Sendable<web_sys::GpuAdapter>;
-    type DeviceId = Sendable<web_sys::GpuDevice>;
+    type DeviceId = Sendable<Device>;
     type QueueId = Sendable<web_sys::GpuQueue>;
     type ShaderModuleId = Sendable<web_sys::GpuShaderModule>;
     type BindGroupLayoutId = Sendable<web_sys::GpuBindGroupLayout>;
     type BindGroupId = Sendable<web_sys::GpuBindGroup>;
     type TextureViewId = Sendable<web_sys::GpuTextureView>;
     type SamplerId = Sendable<web_sys::GpuSampler>;
-    type BufferId = Sendable<web_sys::GpuBuffer>;
+    type BufferId = Sendable<Buffer>;
     type TextureId = Sendable<web_sys::GpuTexture>;
     type PipelineLayoutId = Sendable<web_sys::GpuPipelineLayout>;
     type RenderPipelineId = Sendable<web_sys::GpuRenderPipeline>;
@@ -686,8 +832,8 @@ impl crate::Context for Context {
     type ComputePassId = ComputePass;
     type RenderPassId = RenderPass;
     type CommandBufferId = Sendable<web_sys::GpuCommandBuffer>;
-    type RenderBundleEncoderId = RenderPass; //web_sys::GpuRenderBundleEncoder;
-    type RenderBundleId = (); //web_sys::GpuRenderBundle;
+    type RenderBundleEncoderId = RenderBundleEncoder;
+    type RenderBundleId = Sendable<web_sys::GpuRenderBundle>;
     type SurfaceId = Sendable<web_sys::GpuCanvasContext>;
     type SwapChainId = Sendable<web_sys::GpuSwapChain>;
 
@@ -697,7 +843,8 @@ impl crate::Context for Context {
     type RequestDeviceFuture = MakeSendFuture<
         FutureMap<Result<(Self::DeviceId, Self::QueueId), crate::RequestDeviceError>>,
     >;
-    type MapAsyncFuture = MakeSendFuture<MapFuture<()>>;
+    type MapAsyncFuture = MakeSendFuture<FutureMap<Result<(), crate::BufferAsyncError>>>;
+    type PopErrorScopeFuture = MakeSendFuture<PopErrorScope>;
 
     fn init(_backends: wgt::BackendBit) -> Self {
         Sendable(web_sys::window().unwrap().navigator().gpu())
@@ -765,7 +912,7 @@ impl crate::Context for Context {
             desc.features & crate::Features::ALL_NATIVE
         );
         let mut mapped_desc = web_sys::GpuDeviceDescriptor::new();
-        // TODO: label, extensions
+        // TODO: label, once `wgt::DeviceDescriptor` has one
         let mut mapped_limits = web_sys::GpuLimits::new();
         mapped_limits.max_bind_groups(desc.limits.max_bind_groups);
         mapped_desc.limits(&mapped_limits);
@@ -777,23 +924,23 @@ impl crate::Context for Context {
     }
 
     fn adapter_features(&self, _adapter: &Self::AdapterId) -> wgt::Features {
-        // TODO: web-sys has no way of getting extensions on adapters
+        // Every flag of the pinned `wgt::Features` is a native extension, and the features the
+        // browser lists in `GPUAdapter.features` (`depth-clamping`, `texture-compression-bc`)
+        // have no flag there. Reporting them is blocked on updating wgpu-types.
         wgt::Features::empty()
     }
 
-    fn adapter_limits(&self, _adapter: &Self::AdapterId) -> wgt::Limits {
-        // TODO: web-sys has no way of getting limits on adapters
-        wgt::Limits::default()
+    fn adapter_limits(&self, adapter: &Self::AdapterId) -> wgt::Limits {
+        map_limits(&adapter.0)
     }
 
     fn device_features(&self, _device: &Self::DeviceId) -> wgt::Features {
-        // TODO: web-sys has no way of getting extensions on devices
+        // Blocked on updating wgpu-types, like `adapter_features`.
         wgt::Features::empty()
     }
 
-    fn device_limits(&self, _device: &Self::DeviceId) -> wgt::Limits {
-        // TODO: web-sys has a method for getting limits on devices, but it returns Object not GpuLimit
-        wgt::Limits::default()
+    fn device_limits(&self, device: &Self::DeviceId) -> wgt::Limits {
+        map_limits(&device.0.device)
     }
 
     fn device_create_swap_chain(
@@ -803,7 +950,7 @@ impl crate::Context for Context {
         desc: &wgt::SwapChainDescriptor,
     ) -> Self::SwapChainId {
         let mut mapped =
-            web_sys::GpuSwapChainDescriptor::new(&device.0, map_texture_format(desc.format));
+            web_sys::GpuSwapChainDescriptor::new(&device.0.device, map_texture_format(desc.format));
         mapped.usage(desc.usage.bits());
         Sendable(surface.0.configure_swap_chain(&mapped))
     }
@@ -812,7 +959,7 @@ impl crate::Context for Context {
         &self,
         device: &Self::DeviceId,
         source: ShaderModuleSource,
-    ) -> Self::ShaderModuleId {
+    ) -> Result<Self::ShaderModuleId, CreateError> {
         let desc = match source {
             ShaderModuleSource::SpirV(spv) => {
                 web_sys::GpuShaderModuleDescriptor::new(&js_sys::Uint32Array::from(spv))
@@ -821,15 +968,16 @@ impl crate::Context for Context {
                 panic!("WGSL is not yet supported by the Web backend")
             }
         };
-        // TODO: label
-        Sendable(device.0.create_shader_module(&desc))
+        // TODO: label, once `ShaderModuleSource` comes with one
+        // The browser validates asynchronously, so creation itself never fails here.
+        Ok(Sendable(device.0.device.create_shader_module(&desc)))
     }
 
     fn device_create_bind_group_layout(
         &self,
         device: &Self::DeviceId,
         desc: &BindGroupLayoutDescriptor,
-    ) -> Self::BindGroupLayoutId {
+    ) -> Result<Self::BindGroupLayoutId, CreateError> {
         use web_sys::GpuBindingType as bt;
 
         let mapped_bindings = desc
@@ -900,14 +1048,16 @@ impl crate::Context for Context {
         if let Some(label) = desc.label {
             mapped_desc.label(label);
         }
-        Sendable(device.0.create_bind_group_layout(&mapped_desc))
+        Ok(Sendable(
+            device.0.device.create_bind_group_layout(&mapped_desc),
+        ))
     }
 
     fn device_create_bind_group(
         &self,
         device: &Self::DeviceId,
         desc: &BindGroupDescriptor,
-    ) -> Self::BindGroupId {
+    ) -> Result<Self::BindGroupId, CreateError> {
         let mapped_entries = desc
             .entries
             .iter()
@@ -915,16 +1065,18 @@ impl crate::Context for Context {
                 let mapped_resource = match &binding.resource {
                     BindingResource::Buffer(buffer_slice) => {
                         let mut mapped_buffer_binding =
-                            web_sys::GpuBufferBinding::new(&buffer_slice.buffer.id.0);
+                            web_sys::GpuBufferBinding::new(&real(&buffer_slice.buffer.id).0.buffer);
                         mapped_buffer_binding.offset(buffer_slice.offset as f64);
                         if let Some(s) = buffer_slice.size {
                             mapped_buffer_binding.size(s.get() as f64);
                         }
                         JsValue::from(mapped_buffer_binding.clone())
                     }
-                    BindingResource::Sampler(ref sampler) => JsValue::from(sampler.id.0.clone()),
+                    BindingResource::Sampler(ref sampler) => {
+                        JsValue::from(real(&sampler.id).0.clone())
+                    }
                     BindingResource::TextureView(ref texture_view) => {
-                        JsValue::from(texture_view.id.0.clone())
+                        JsValue::from(real(&texture_view.id).0.clone())
                     }
                     BindingResource::TextureViewArray(..) => {
                         panic!("Web backend does not support BINDING_INDEXING extension")
@@ -936,33 +1088,35 @@ impl crate::Context for Context {
             .collect::<js_sys::Array>();
 
         let mut mapped_desc =
-            web_sys::GpuBindGroupDescriptor::new(&mapped_entries, &desc.layout.id.0);
+            web_sys::GpuBindGroupDescriptor::new(&mapped_entries, &real(&desc.layout.id).0);
         if let Some(label) = desc.label {
             mapped_desc.label(label);
         }
-        Sendable(device.0.create_bind_group(&mapped_desc))
+        Ok(Sendable(device.0.device.create_bind_group(&mapped_desc)))
     }
 
     fn device_create_pipeline_layout(
         &self,
         device: &Self::DeviceId,
         desc: &PipelineLayoutDescriptor,
-    ) -> Self::PipelineLayoutId {
+    ) -> Result<Self::PipelineLayoutId, CreateError> {
         let temp_layouts = desc
             .bind_group_layouts
             .iter()
-            .map(|bgl| bgl.id.0.clone())
+            .map(|bgl| real(&bgl.id).0.clone())
             .collect::<js_sys::Array>();
         let mapped_desc = web_sys::GpuPipelineLayoutDescriptor::new(&temp_layouts);
-        // TODO: label
-        Sendable(device.0.create_pipeline_layout(&mapped_desc))
+        // TODO: label, once `wgt::PipelineLayoutDescriptor` has one
+        Ok(Sendable(
+            device.0.device.create_pipeline_layout(&mapped_desc),
+        ))
     }
 
     fn device_create_render_pipeline(
         &self,
         device: &Self::DeviceId,
         desc: &RenderPipelineDescriptor,
-    ) -> Self::RenderPipelineId {
+    ) -> Result<Self::RenderPipelineId, CreateError> {
         use web_sys::GpuPrimitiveTopology as pt;
 
         let mapped_color_states = desc
@@ -992,13 +1146,13 @@ impl crate::Context for Context {
         let mapped_vertex_stage = map_stage_descriptor(&desc.vertex_stage);
 
         let mut mapped_desc = web_sys::GpuRenderPipelineDescriptor::new(
-            &desc.layout.id.0,
+            &real(&desc.layout.id).0,
             &mapped_color_states,
             mapped_primitive_topology,
             &mapped_vertex_stage,
         );
 
-        // TODO: label
+        // TODO: label, once `wgt::RenderPipelineDescriptor` has one
 
         if let Some(ref frag) = desc.fragment_stage {
             mapped_desc.fragment_stage(&map_stage_descriptor(frag));
@@ -1017,39 +1171,60 @@ impl crate::Context for Context {
         mapped_desc.sample_mask(desc.sample_mask);
         mapped_desc.alpha_to_coverage_enabled(desc.alpha_to_coverage_enabled);
 
-        Sendable(device.0.create_render_pipeline(&mapped_desc))
+        Ok(Sendable(
+            device.0.device.create_render_pipeline(&mapped_desc),
+        ))
     }
 
     fn device_create_compute_pipeline(
         &self,
         device: &Self::DeviceId,
         desc: &ComputePipelineDescriptor,
-    ) -> Self::ComputePipelineId {
+    ) -> Result<Self::ComputePipelineId, CreateError> {
         let mapped_compute_stage = map_stage_descriptor(&desc.compute_stage);
-        let mapped_desc =
-            web_sys::GpuComputePipelineDescriptor::new(&desc.layout.id.0, &mapped_compute_stage);
-        // TODO: label
-        Sendable(device.0.create_compute_pipeline(&mapped_desc))
+        let mapped_desc = web_sys::GpuComputePipelineDescriptor::new(
+            &real(&desc.layout.id).0,
+            &mapped_compute_stage,
+        );
+        // TODO: label, once `wgt::ComputePipelineDescriptor` has one
+        Ok(Sendable(
+            device.0.device.create_compute_pipeline(&mapped_desc),
+        ))
     }
 
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
         desc: &BufferDescriptor,
-    ) -> Self::BufferId {
+    ) -> Result<Self::BufferId, CreateError> {
         let mut mapped_desc =
             web_sys::GpuBufferDescriptor::new(desc.size as f64, desc.usage.bits());
         if let Some(label) = desc.label {
             mapped_desc.label(label);
         }
-        Sendable(device.0.create_buffer(&mapped_desc))
+        if desc.mapped_at_creation {
+            set_property(&mapped_desc, "mappedAtCreation", &true.into());
+        }
+        let mapping = if desc.mapped_at_creation {
+            Some(BufferMapping {
+                range: 0..desc.size,
+                writable: true,
+                shadow: None,
+            })
+        } else {
+            None
+        };
+        Ok(Sendable(Buffer {
+            buffer: device.0.device.create_buffer(&mapped_desc),
+            mapping: RefCell::new(mapping),
+        }))
     }
 
     fn device_create_texture(
         &self,
         device: &Self::DeviceId,
         desc: &TextureDescriptor,
-    ) -> Self::TextureId {
+    ) -> Result<Self::TextureId, CreateError> {
         let mut mapped_desc = web_sys::GpuTextureDescriptor::new(
             map_texture_format(desc.format),
             &map_extent_3d(desc.size),
@@ -1061,16 +1236,18 @@ impl crate::Context for Context {
         mapped_desc.dimension(map_texture_dimension(desc.dimension));
         mapped_desc.mip_level_count(desc.mip_level_count);
         mapped_desc.sample_count(desc.sample_count);
-        Sendable(device.0.create_texture(&mapped_desc))
+        Ok(Sendable(device.0.device.create_texture(&mapped_desc)))
     }
 
     fn device_create_sampler(
         &self,
         device: &Self::DeviceId,
         desc: &SamplerDescriptor,
-    ) -> Self::SamplerId {
+    ) -> Result<Self::SamplerId, CreateError> {
         let mut mapped_desc = web_sys::GpuSamplerDescriptor::new();
-        // TODO: label
+        if let Some(label) = desc.label {
+            mapped_desc.label(label);
+        }
         mapped_desc.address_mode_u(map_address_mode(desc.address_mode_u));
         mapped_desc.address_mode_v(map_address_mode(desc.address_mode_v));
         mapped_desc.address_mode_w(map_address_mode(desc.address_mode_w));
@@ -1082,7 +1259,9 @@ impl crate::Context for Context {
         mapped_desc.mag_filter(map_filter_mode(desc.mag_filter));
         mapped_desc.min_filter(map_filter_mode(desc.min_filter));
         mapped_desc.mipmap_filter(map_filter_mode(desc.mipmap_filter));
-        Sendable(device.0.create_sampler_with_descriptor(&mapped_desc))
+        Ok(Sendable(
+            device.0.device.create_sampler_with_descriptor(&mapped_desc),
+        ))
     }
 
     fn device_create_command_encoder(
@@ -1101,10 +1280,23 @@ impl crate::Context for Context {
 
     fn device_create_render_bundle_encoder(
         &self,
-        _device: &Self::DeviceId,
-        _desc: &wgt::RenderBundleEncoderDescriptor,
+        device: &Self::DeviceId,
+        desc: &wgt::RenderBundleEncoderDescriptor,
     ) -> Self::RenderBundleEncoderId {
-        unimplemented!()
+        let mapped_color_formats = desc
+            .color_formats
+            .iter()
+            .map(|format| JsValue::from(map_texture_format(*format)))
+            .collect::<js_sys::Array>();
+        let mut mapped_desc = web_sys::GpuRenderBundleEncoderDescriptor::new(&mapped_color_formats);
+        if let Some(format) = desc.depth_stencil_format {
+            mapped_desc.depth_stencil_format(map_texture_format(format));
+        }
+        mapped_desc.sample_count(desc.sample_count);
+        if let Some(label) = desc.label {
+            mapped_desc.label(label);
+        }
+        RenderBundleEncoder(device.0.device.create_render_bundle_encoder(&mapped_desc))
     }
 
     fn device_drop(&self, _device: &Self::DeviceId) {
@@ -1115,38 +1307,100 @@ impl crate::Context for Context {
         // Device is polled automatically
     }
 
+    fn device_push_error_scope(&self, device: &Self::DeviceId, filter: crate::ErrorFilter) {
+        device.0.device.push_error_scope(map_error_filter(filter));
+        device.0.error_sink.lock().push_scope(filter);
+    }
+
+    fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture {
+        let shadow = device.0.error_sink.lock().pop_scope();
+        let error_promise = device.0.device.pop_error_scope();
+        MakeSendFuture(PopErrorScope {
+            shadow,
+            browser: wasm_bindgen_futures::JsFuture::from(error_promise)
+                .map(future_pop_error_scope),
+        })
+    }
+
+    fn device_on_uncaptured_error(
+        &self,
+        device: &Self::DeviceId,
+        handler: Box<dyn Fn(crate::Error) + Send + Sync>,
+    ) {
+        let handler: Arc<dyn Fn(crate::Error) + Send + Sync> = handler.into();
+        device
+            .0
+            .error_sink
+            .lock()
+            .set_uncaptured_handler(Arc::clone(&handler));
+        let closure = Closure::wrap(Box::new(move |event: web_sys::GpuUncapturedErrorEvent| {
+            handler(map_error(&event.error()));
+        }) as Box<dyn FnMut(_)>);
+        device
+            .0
+            .device
+            .set_onuncapturederror(Some(closure.as_ref().unchecked_ref()));
+        // The device no longer refers to the previous callback, so it can be dropped.
+        *device.0.uncaptured_handler.borrow_mut() = Some(closure);
+    }
+
+    fn device_capture_error(&self, device: &Self::DeviceId, error: crate::Error) {
+        // The browser has no way to add an error to its scopes, so it goes to their shadow.
+        let _ = device.0.error_sink.lock().capture(error);
+    }
+
+    fn device_report_error(&self, device: &Self::DeviceId, error: crate::Error) {
+        error::handle_error(&device.0.error_sink, error);
+    }
+
     fn buffer_map_async(
         &self,
-        _buffer: &Self::BufferId,
-        _mode: crate::MapMode,
-        _range: Range<wgt::BufferAddress>,
+        buffer: &Self::BufferId,
+        mode: crate::MapMode,
+        range: Range<wgt::BufferAddress>,
     ) -> Self::MapAsyncFuture {
-        unimplemented!()
-        //MakeSendFuture(MapFuture {
-        //    child: wasm_bindgen_futures::JsFuture::from(buffer.0.map_async()),
-        //    buffer: Some(buffer.0.clone()),
-        //    marker: PhantomData,
-        //})
+        let mode_bits = match mode {
+            crate::MapMode::Read => GPU_MAP_MODE_READ,
+            crate::MapMode::Write => GPU_MAP_MODE_WRITE,
+        };
+        let map_promise = buffer.0.buffer.unchecked_ref::<GpuBufferExt>().map_async(
+            mode_bits,
+            range.start as f64,
+            (range.end - range.start) as f64,
+        );
+        *buffer.0.mapping.borrow_mut() = Some(BufferMapping {
+            range,
+            writable: mode == crate::MapMode::Write,
+            shadow: None,
+        });
+        MakeSendFuture(wasm_bindgen_futures::JsFuture::from(map_promise).map(future_map_async))
     }
 
     fn buffer_get_mapped_range(
         &self,
-        _buffer: &Self::BufferId,
-        _sub_range: Range<wgt::BufferAddress>,
+        buffer: &Self::BufferId,
+        sub_range: Range<wgt::BufferAddress>,
     ) -> &[u8] {
-        unimplemented!()
+        let ptr = buffer.0.mapped_ptr(&sub_range);
+        unsafe { slice::from_raw_parts(ptr, (sub_range.end - sub_range.start) as usize) }
     }
 
     fn buffer_get_mapped_range_mut(
         &self,
-        _buffer: &Self::BufferId,
-        _sub_range: Range<wgt::BufferAddress>,
+        buffer: &Self::BufferId,
+        sub_range: Range<wgt::BufferAddress>,
     ) -> &mut [u8] {
-        unimplemented!()
+        let ptr = buffer.0.mapped_ptr(&sub_range);
+        unsafe { slice::from_raw_parts_mut(ptr, (sub_range.end - sub_range.start) as usize) }
     }
 
     fn buffer_unmap(&self, buffer: &Self::BufferId) {
-        buffer.0.unmap();
+        if let Some(mapping) = buffer.0.mapping.borrow_mut().take() {
+            if let (true, Some((array_buffer, shadow))) = (mapping.writable, mapping.shadow) {
+                js_sys::Uint8Array::new(&array_buffer).copy_from(&shadow);
+            }
+        }
+        buffer.0.buffer.unmap();
     }
 
     fn swap_chain_get_next_texture(
@@ -1189,7 +1443,9 @@ impl crate::Context for Context {
                 mapped_desc.dimension(map_texture_view_dimension(d.dimension));
                 mapped_desc.format(map_texture_format(d.format));
                 mapped_desc.mip_level_count(d.level_count);
-                // TODO: label
+                if let Some(label) = d.label {
+                    mapped_desc.label(label);
+                }
                 texture.0.create_view_with_descriptor(&mapped_desc)
             }
             None => texture.0.create_view(),
@@ -1243,9 +1499,9 @@ impl crate::Context for Context {
         copy_size: wgt::BufferAddress,
     ) {
         encoder.copy_buffer_to_buffer_with_f64_and_f64_and_f64(
-            &source.0,
+            &source.0.buffer,
             source_offset as f64,
-            &destination.0,
+            &destination.0.buffer,
             destination_offset as f64,
             copy_size as f64,
         )
@@ -1297,10 +1553,8 @@ impl crate::Context for Context {
         &self,
         encoder: &Self::CommandEncoderId,
     ) -> Self::ComputePassId {
-        let mut mapped_desc = web_sys::GpuComputePassDescriptor::new();
-        if let Some(ref label) = encoder.label() {
-            mapped_desc.label(label);
-        }
+        // TODO: label, once compute passes have a descriptor
+        let mapped_desc = web_sys::GpuComputePassDescriptor::new();
         ComputePass(encoder.begin_compute_pass_with_descriptor(&mapped_desc))
     }
 
@@ -1323,7 +1577,7 @@ impl crate::Context for Context {
             .map(|ca| {
                 let mut mapped_color_attachment =
                     web_sys::GpuRenderPassColorAttachmentDescriptor::new(
-                        &ca.attachment.id.0,
+                        &real(&ca.attachment.id).0,
                         &match ca.ops.load {
                             LoadOp::Clear(color) => wasm_bindgen::JsValue::from(map_color(color)),
                             LoadOp::Load => wasm_bindgen::JsValue::from(web_sys::GpuLoadOp::Load),
@@ -1331,7 +1585,7 @@ impl crate::Context for Context {
                     );
 
                 if let Some(rt) = ca.resolve_target {
-                    mapped_color_attachment.resolve_target(&rt.id.0);
+                    mapped_color_attachment.resolve_target(&real(&rt.id).0);
                 }
 
                 mapped_color_attachment.store_op(map_store_op(ca.ops.store));
@@ -1340,10 +1594,9 @@ impl crate::Context for Context {
             })
             .collect::<js_sys::Array>();
 
+        // TODO: label, once `wgt::RenderPassDescriptor` has one
         let mut mapped_desc = web_sys::GpuRenderPassDescriptor::new(&mapped_color_attachments);
 
-        // TODO: label
-
         if let Some(dsa) = &desc.depth_stencil_attachment {
             let (depth_load_op, depth_store_op) = match dsa.depth_ops {
                 Some(ref ops) => {
@@ -1373,7 +1626,7 @@ impl crate::Context for Context {
             };
             let mapped_depth_stencil_attachment =
                 web_sys::GpuRenderPassDepthStencilAttachmentDescriptor::new(
-                    &dsa.attachment.id.0,
+                    &real(&dsa.attachment.id).0,
                     &depth_load_op,
                     depth_store_op,
                     &stencil_load_op,
@@ -1404,31 +1657,43 @@ impl crate::Context for Context {
 
     fn render_bundle_encoder_finish(
         &self,
-        _encoder: Self::RenderBundleEncoderId,
-        _desc: &crate::RenderBundleDescriptor,
+        encoder: Self::RenderBundleEncoderId,
+        desc: &crate::RenderBundleDescriptor,
     ) -> Self::RenderBundleId {
-        unimplemented!()
+        let mut mapped_desc = web_sys::GpuRenderBundleDescriptor::new();
+        if let Some(label) = desc.label {
+            mapped_desc.label(label);
+        }
+        Sendable(encoder.0.finish_with_descriptor(&mapped_desc))
     }
 
     fn queue_write_buffer(
         &self,
-        _queue: &Self::QueueId,
-        _buffer: &Self::BufferId,
-        _offset: wgt::BufferAddress,
-        _data: &[u8],
+        queue: &Self::QueueId,
+        buffer: &Self::BufferId,
+        offset: wgt::BufferAddress,
+        data: &[u8],
     ) {
-        unimplemented!()
+        queue
+            .0
+            .unchecked_ref::<GpuQueueExt>()
+            .write_buffer(&buffer.0.buffer, offset as f64, data);
     }
 
     fn queue_write_texture(
         &self,
-        _queue: &Self::QueueId,
-        _texture: crate::TextureCopyView,
-        _data: &[u8],
-        _data_layout: wgt::TextureDataLayout,
-        _size: wgt::Extent3d,
+        queue: &Self::QueueId,
+        texture: crate::TextureCopyView,
+        data: &[u8],
+        data_layout: wgt::TextureDataLayout,
+        size: wgt::Extent3d,
     ) {
-        unimplemented!()
+        queue.0.unchecked_ref::<GpuQueueExt>().write_texture(
+            &map_texture_copy_view(texture),
+            data,
+            &map_texture_data_layout(data_layout),
+            &map_extent_3d(size),
+        );
     }
 
     fn queue_submit<I: Iterator<Item = Self::CommandBufferId>>(
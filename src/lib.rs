@@ -6,13 +6,19 @@
 #![warn(missing_docs)]
 
 mod backend;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod util;
 #[macro_use]
 mod macros;
+#[cfg(all(test, feature = "mock"))]
+mod tests;
+mod validation;
 
 use std::{
     future::Future,
     marker::PhantomData,
+    mem,
     ops::{Bound, Range, RangeBounds},
     sync::Arc,
     thread,
@@ -57,6 +63,11 @@ trait ComputePassInner<Ctx: Context> {
     );
 }
 
+/// Size of a `DrawIndirect` structure in an indirect buffer.
+const DRAW_INDIRECT_SIZE: BufferAddress = 16;
+/// Size of a `DrawIndexedIndirect` structure in an indirect buffer.
+const DRAW_INDEXED_INDIRECT_SIZE: BufferAddress = 20;
+
 trait RenderInner<Ctx: Context> {
     fn set_pipeline(&mut self, pipeline: &Ctx::RenderPipelineId);
     fn set_bind_group(
@@ -87,34 +98,31 @@ trait RenderInner<Ctx: Context> {
         indirect_buffer: &Ctx::BufferId,
         indirect_offset: BufferAddress,
     );
+    // The multi-draw calls default to a loop of single indirect draws, for backends
+    // without native support.
     fn multi_draw_indirect(
         &mut self,
         indirect_buffer: &Ctx::BufferId,
         indirect_offset: BufferAddress,
         count: u32,
-    );
+    ) {
+        for i in 0..count as BufferAddress {
+            self.draw_indirect(indirect_buffer, indirect_offset + i * DRAW_INDIRECT_SIZE);
+        }
+    }
     fn multi_draw_indexed_indirect(
         &mut self,
         indirect_buffer: &Ctx::BufferId,
         indirect_offset: BufferAddress,
         count: u32,
-    );
-    fn multi_draw_indirect_count(
-        &mut self,
-        indirect_buffer: &Ctx::BufferId,
-        indirect_offset: BufferAddress,
-        count_buffer: &Ctx::BufferId,
-        count_buffer_offset: BufferAddress,
-        max_count: u32,
-    );
-    fn multi_draw_indexed_indirect_count(
-        &mut self,
-        indirect_buffer: &Ctx::BufferId,
-        indirect_offset: BufferAddress,
-        count_buffer: &Ctx::BufferId,
-        count_buffer_offset: BufferAddress,
-        max_count: u32,
-    );
+    ) {
+        for i in 0..count as BufferAddress {
+            self.draw_indexed_indirect(
+                indirect_buffer,
+                indirect_offset + i * DRAW_INDEXED_INDIRECT_SIZE,
+            );
+        }
+    }
 }
 
 trait RenderPassInner<Ctx: Context>: RenderInner<Ctx> {
@@ -133,6 +141,22 @@ trait RenderPassInner<Ctx: Context>: RenderInner<Ctx> {
     fn insert_debug_marker(&mut self, label: &str);
     fn push_debug_group(&mut self, group_label: &str);
     fn pop_debug_group(&mut self);
+    fn multi_draw_indirect_count(
+        &mut self,
+        indirect_buffer: &Ctx::BufferId,
+        indirect_offset: BufferAddress,
+        count_buffer: &Ctx::BufferId,
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    );
+    fn multi_draw_indexed_indirect_count(
+        &mut self,
+        indirect_buffer: &Ctx::BufferId,
+        indirect_offset: BufferAddress,
+        count_buffer: &Ctx::BufferId,
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    );
     fn execute_bundles<'a, I: Iterator<Item = &'a Ctx::RenderBundleId>>(
         &mut self,
         render_bundles: I,
@@ -168,6 +192,7 @@ trait Context: Sized {
     type RequestDeviceFuture: Future<Output = Result<(Self::DeviceId, Self::QueueId), RequestDeviceError>>
         + Send;
     type MapAsyncFuture: Future<Output = Result<(), BufferAsyncError>> + Send;
+    type PopErrorScopeFuture: Future<Output = Option<Error>> + Send;
 
     fn init(backends: BackendBit) -> Self;
     fn instance_create_surface(
@@ -199,47 +224,47 @@ trait Context: Sized {
         &self,
         device: &Self::DeviceId,
         source: ShaderModuleSource,
-    ) -> Self::ShaderModuleId;
+    ) -> Result<Self::ShaderModuleId, CreateError>;
     fn device_create_bind_group_layout(
         &self,
         device: &Self::DeviceId,
         desc: &BindGroupLayoutDescriptor,
-    ) -> Self::BindGroupLayoutId;
+    ) -> Result<Self::BindGroupLayoutId, CreateError>;
     fn device_create_bind_group(
         &self,
         device: &Self::DeviceId,
         desc: &BindGroupDescriptor,
-    ) -> Self::BindGroupId;
+    ) -> Result<Self::BindGroupId, CreateError>;
     fn device_create_pipeline_layout(
         &self,
         device: &Self::DeviceId,
         desc: &PipelineLayoutDescriptor,
-    ) -> Self::PipelineLayoutId;
+    ) -> Result<Self::PipelineLayoutId, CreateError>;
     fn device_create_render_pipeline(
         &self,
         device: &Self::DeviceId,
         desc: &RenderPipelineDescriptor,
-    ) -> Self::RenderPipelineId;
+    ) -> Result<Self::RenderPipelineId, CreateError>;
     fn device_create_compute_pipeline(
         &self,
         device: &Self::DeviceId,
         desc: &ComputePipelineDescriptor,
-    ) -> Self::ComputePipelineId;
+    ) -> Result<Self::ComputePipelineId, CreateError>;
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
         desc: &BufferDescriptor,
-    ) -> Self::BufferId;
+    ) -> Result<Self::BufferId, CreateError>;
     fn device_create_texture(
         &self,
         device: &Self::DeviceId,
         desc: &TextureDescriptor,
-    ) -> Self::TextureId;
+    ) -> Result<Self::TextureId, CreateError>;
     fn device_create_sampler(
         &self,
         device: &Self::DeviceId,
         desc: &SamplerDescriptor,
-    ) -> Self::SamplerId;
+    ) -> Result<Self::SamplerId, CreateError>;
     fn device_create_command_encoder(
         &self,
         device: &Self::DeviceId,
@@ -252,6 +277,15 @@ trait Context: Sized {
     ) -> Self::RenderBundleEncoderId;
     fn device_drop(&self, device: &Self::DeviceId);
     fn device_poll(&self, device: &Self::DeviceId, maintain: Maintain);
+    fn device_push_error_scope(&self, device: &Self::DeviceId, filter: ErrorFilter);
+    fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture;
+    fn device_on_uncaptured_error(
+        &self,
+        device: &Self::DeviceId,
+        handler: Box<dyn Fn(Error) + Send + Sync>,
+    );
+    fn device_capture_error(&self, device: &Self::DeviceId, error: Error);
+    fn device_report_error(&self, device: &Self::DeviceId, error: Error);
 
     fn buffer_map_async(
         &self,
@@ -420,13 +454,20 @@ pub enum Maintain {
 }
 
 /// The main purpose of this struct is to resolve mapped ranges
-/// (convert sizes to end points), and to ensure that the sub-ranges
-/// don't intersect.
+/// (convert sizes to end points), and to ensure that writable sub-ranges
+/// don't intersect any other sub-range.
 #[derive(Debug)]
 struct MapContext {
     total_size: BufferAddress,
     initial_range: Range<BufferAddress>,
-    sub_ranges: Vec<Range<BufferAddress>>,
+    /// Whether `initial_range` is waiting for a `map_async` to resolve.
+    pending: bool,
+    /// Number of `map_async` calls so far, to tell a pending map from the ones before it.
+    generation: u64,
+    /// The future of a pending map was dropped, so the backend may still map the buffer.
+    abandoned: bool,
+    /// Ranges of the live views, and whether they are writable.
+    sub_ranges: Vec<(Range<BufferAddress>, bool)>,
 }
 
 impl MapContext {
@@ -434,37 +475,76 @@ impl MapContext {
         MapContext {
             total_size,
             initial_range: 0..0,
+            pending: false,
+            generation: 0,
+            abandoned: false,
             sub_ranges: Vec::new(),
         }
     }
 
-    fn reset(&mut self) {
+    /// Unmaps the buffer, cancelling a pending map.
+    fn reset(&mut self) -> Result<(), MapError> {
+        if !self.sub_ranges.is_empty() {
+            return Err(MapError::ViewsAlive);
+        }
         self.initial_range = 0..0;
+        self.pending = false;
+        self.abandoned = false;
+        Ok(())
+    }
 
-        assert!(
-            self.sub_ranges.is_empty(),
-            "You cannot unmap a buffer that still has accessible mapped views"
-        );
+    /// Completes the map of `generation`, unless it was cancelled or abandoned since.
+    fn resolve(
+        &mut self,
+        generation: u64,
+        result: Result<(), BufferAsyncError>,
+    ) -> Result<(), BufferAsyncError> {
+        if !self.pending || self.generation != generation {
+            return Err(BufferAsyncError);
+        }
+        self.pending = false;
+        if result.is_err() {
+            self.initial_range = 0..0;
+        }
+        result
     }
 
-    fn add(&mut self, offset: BufferAddress, size: Option<BufferSize>) -> BufferAddress {
+    /// Forgets the map of `generation` if it is still pending.
+    fn abandon(&mut self, generation: u64) {
+        if self.pending && self.generation == generation {
+            self.initial_range = 0..0;
+            self.pending = false;
+            self.abandoned = true;
+        }
+    }
+
+    fn add(
+        &mut self,
+        offset: BufferAddress,
+        size: Option<BufferSize>,
+        writable: bool,
+    ) -> Result<BufferAddress, MapError> {
+        if self.pending || self.initial_range.start == self.initial_range.end {
+            return Err(MapError::NotMapped);
+        }
         let end = match size {
             Some(s) => offset + s.get(),
             None => self.initial_range.end,
         };
-        assert!(self.initial_range.start <= offset && end <= self.initial_range.end);
-        for sub in self.sub_ranges.iter() {
-            assert!(
-                end <= sub.start || offset >= sub.end,
-                "Intersecting map range with {:?}",
-                sub
-            );
+        if offset < self.initial_range.start || end > self.initial_range.end || offset > end {
+            return Err(MapError::OutOfRange(offset..end));
+        }
+        // Read-only views may overlap each other, writable ones are exclusive.
+        if let Some((sub, _)) = self.sub_ranges.iter().find(|(sub, sub_writable)| {
+            (writable || *sub_writable) && end > sub.start && offset < sub.end
+        }) {
+            return Err(MapError::Overlap(sub.clone()));
         }
-        self.sub_ranges.push(offset..end);
-        end
+        self.sub_ranges.push((offset..end, writable));
+        Ok(end)
     }
 
-    fn remove(&mut self, offset: BufferAddress, size: Option<BufferSize>) {
+    fn remove(&mut self, offset: BufferAddress, size: Option<BufferSize>, writable: bool) {
         let end = match size {
             Some(s) => offset + s.get(),
             None => self.initial_range.end,
@@ -474,19 +554,34 @@ impl MapContext {
         let index = self
             .sub_ranges
             .iter()
-            .position(|r| *r == (offset..end))
+            .position(|r| *r == (offset..end, writable))
             .expect("unable to remove range from map context");
         self.sub_ranges.swap_remove(index);
     }
 }
 
+/// Abandons a pending map if the future of [`BufferSlice::map_async`] is dropped unresolved.
+struct PendingMap {
+    map_context: Arc<Mutex<MapContext>>,
+    generation: u64,
+    resolved: bool,
+}
+
+impl Drop for PendingMap {
+    fn drop(&mut self) {
+        if !self.resolved {
+            self.map_context.lock().abandon(self.generation);
+        }
+    }
+}
+
 /// Handle to a GPU-accessible buffer.
 ///
 /// Created with [`Device::create_buffer`] or [`Device::create_buffer_with_data`]
 pub struct Buffer {
     context: Arc<C>,
     id: <C as Context>::BufferId,
-    map_context: Mutex<MapContext>,
+    map_context: Arc<Mutex<MapContext>>,
     usage: BufferUsage,
 }
 
@@ -509,6 +604,10 @@ pub struct Texture {
     context: Arc<C>,
     id: <C as Context>::TextureId,
     owned: bool,
+    size: Extent3d,
+    mip_level_count: u32,
+    dimension: TextureDimension,
+    format: TextureFormat,
 }
 
 /// Handle to a texture view.
@@ -957,13 +1056,13 @@ impl Instance {
     #[cfg(not(target_arch = "wasm32"))]
     pub fn enumerate_adapters(&self, backends: BackendBit) -> impl Iterator<Item = Adapter> {
         let context = Arc::clone(&self.context);
-        self.context
+        backend::real(&*self.context)
             .enumerate_adapters(wgc::instance::AdapterInputs::Mask(backends, |_| {
                 PhantomData
             }))
             .into_iter()
             .map(move |id| crate::Adapter {
-                id,
+                id: backend::from_real(id),
                 context: Arc::clone(&context),
             })
     }
@@ -1002,22 +1101,23 @@ impl Instance {
         &self,
         layer: *mut std::ffi::c_void,
     ) -> Surface {
+        let context = backend::real(&*self.context);
         let surface = wgc::instance::Surface {
             #[cfg(feature = "vulkan-portability")]
-            vulkan: self.context.instance.vulkan.as_ref().map(|inst| {
+            vulkan: context.instance.vulkan.as_ref().map(|inst| {
                 inst.create_surface_from_layer(layer as *mut _, cfg!(debug_assertions))
             }),
-            metal: self.context.instance.metal.as_ref().map(|inst| {
+            metal: context.instance.metal.as_ref().map(|inst| {
                 inst.create_surface_from_layer(layer as *mut _, cfg!(debug_assertions))
             }),
         };
 
         crate::Surface {
-            id: self.context.surfaces.register_identity(
+            id: backend::from_real(context.surfaces.register_identity(
                 PhantomData,
                 surface,
                 &mut wgc::hub::Token::root(),
-            ),
+            )),
         }
     }
 }
@@ -1080,8 +1180,9 @@ impl Adapter {
     /// Get info about the adapter itself.
     #[cfg(not(target_arch = "wasm32"))]
     pub fn get_info(&self) -> AdapterInfo {
-        let context = &self.context;
-        wgc::gfx_select!(self.id => context.adapter_get_info(self.id))
+        let context = backend::real(&*self.context);
+        let id = *backend::real(&self.id);
+        wgc::gfx_select!(id => context.adapter_get_info(id))
     }
 }
 
@@ -1108,11 +1209,41 @@ impl Device {
     }
 
     /// Creates a shader module from either SPIR-V or WGSL source code.
+    ///
+    /// # Panics
+    ///
+    /// - The shader source fails validation, after the error reached an error scope or the
+    ///   handler set with [`Device::on_uncaptured_error`]. Use
+    ///   [`Device::try_create_shader_module`] to handle the error instead.
     pub fn create_shader_module(&self, source: ShaderModuleSource) -> ShaderModule {
-        ShaderModule {
+        self.report(
+            self.create_shader_module_impl(source),
+            "Device::create_shader_module",
+        )
+    }
+
+    /// Creates a shader module from either SPIR-V or WGSL source code,
+    /// returning an error if the module is invalid.
+    ///
+    /// The header of SPIR-V modules is checked before reaching the backend, see
+    /// [`CreateError`] for what is left to it. WGSL modules are only validated by the
+    /// backend, and the native one still panics on invalid WGSL.
+    pub fn try_create_shader_module(
+        &self,
+        source: ShaderModuleSource,
+    ) -> Result<ShaderModule, CreateError> {
+        self.capture(self.create_shader_module_impl(source))
+    }
+
+    fn create_shader_module_impl(
+        &self,
+        source: ShaderModuleSource,
+    ) -> Result<ShaderModule, CreateError> {
+        validation::validate_shader_module(&source)?;
+        Ok(ShaderModule {
             context: Arc::clone(&self.context),
-            id: Context::device_create_shader_module(&*self.context, &self.id, source),
-        }
+            id: Context::device_create_shader_module(&*self.context, &self.id, source)?,
+        })
     }
 
     /// Creates an empty [`CommandEncoder`].
@@ -1138,57 +1269,206 @@ impl Device {
     }
 
     /// Creates a new [`BindGroup`].
+    ///
+    /// # Panics
+    ///
+    /// - The descriptor fails validation, after the error reached an error scope or the
+    ///   handler set with [`Device::on_uncaptured_error`]. Use
+    ///   [`Device::try_create_bind_group`] to handle the error instead.
     pub fn create_bind_group(&self, desc: &BindGroupDescriptor) -> BindGroup {
-        BindGroup {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_bind_group(&*self.context, &self.id, desc),
+        self.report(
+            self.create_bind_group_impl(desc),
+            "Device::create_bind_group",
+        )
+    }
+
+    /// Creates a new [`BindGroup`], returning an error if the descriptor is invalid.
+    pub fn try_create_bind_group(
+        &self,
+        desc: &BindGroupDescriptor,
+    ) -> Result<BindGroup, CreateError> {
+        self.capture(self.create_bind_group_impl(desc))
+    }
+
+    fn create_bind_group_impl(&self, desc: &BindGroupDescriptor) -> Result<BindGroup, CreateError> {
+        let uses_arrays = desc.entries.iter().any(|entry| match entry.resource {
+            BindingResource::TextureViewArray(..) => true,
+            _ => false,
+        });
+        if uses_arrays {
+            self.require_features(Features::SAMPLED_TEXTURE_BINDING_ARRAY)?;
         }
+        Ok(BindGroup {
+            context: Arc::clone(&self.context),
+            id: Context::device_create_bind_group(&*self.context, &self.id, desc)?,
+        })
     }
 
     /// Creates a [`BindGroupLayout`].
+    ///
+    /// # Panics
+    ///
+    /// - The descriptor fails validation, after the error reached an error scope or the
+    ///   handler set with [`Device::on_uncaptured_error`]. Use
+    ///   [`Device::try_create_bind_group_layout`] to handle the error instead.
     pub fn create_bind_group_layout(&self, desc: &BindGroupLayoutDescriptor) -> BindGroupLayout {
-        BindGroupLayout {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_bind_group_layout(&*self.context, &self.id, desc),
+        self.report(
+            self.create_bind_group_layout_impl(desc),
+            "Device::create_bind_group_layout",
+        )
+    }
+
+    /// Creates a [`BindGroupLayout`], returning an error if the descriptor is invalid.
+    pub fn try_create_bind_group_layout(
+        &self,
+        desc: &BindGroupLayoutDescriptor,
+    ) -> Result<BindGroupLayout, CreateError> {
+        self.capture(self.create_bind_group_layout_impl(desc))
+    }
+
+    fn create_bind_group_layout_impl(
+        &self,
+        desc: &BindGroupLayoutDescriptor,
+    ) -> Result<BindGroupLayout, CreateError> {
+        if desc.entries.iter().any(|entry| entry.count.is_some()) {
+            self.require_features(Features::SAMPLED_TEXTURE_BINDING_ARRAY)?;
         }
+        Ok(BindGroupLayout {
+            context: Arc::clone(&self.context),
+            id: Context::device_create_bind_group_layout(&*self.context, &self.id, desc)?,
+        })
     }
 
     /// Creates a [`PipelineLayout`].
+    ///
+    /// # Panics
+    ///
+    /// - The descriptor fails validation, after the error reached an error scope or the
+    ///   handler set with [`Device::on_uncaptured_error`]. Use
+    ///   [`Device::try_create_pipeline_layout`] to handle the error instead.
     pub fn create_pipeline_layout(&self, desc: &PipelineLayoutDescriptor) -> PipelineLayout {
-        PipelineLayout {
-            context: Arc::clone(&self.context),
-            id: Context::device_create_pipeline_layout(&*self.context, &self.id, desc),
+        self.report(
+            self.create_pipeline_layout_impl(desc),
+            "Device::create_pipeline_layout",
+        )
+    }
+
+    /// Creates a [`PipelineLayout`], returning an error if the descriptor is invalid.
+    pub fn try_create_pipeline_layout(
+        &self,
+        desc: &PipelineLayoutDescriptor,
+    ) -> Result<PipelineLayout, CreateError> {
+        self.capture(self.create_pipeline_layout_impl(desc))
+    }
+
+    fn create_pipeline_layout_impl(
+        &self,
+        desc: &PipelineLayoutDescriptor,
+    ) -> Result<PipelineLayout, CreateError> {
+        if !desc.push_constant_ranges.is_empty() {
+            self.require_features(Features::PUSH_CONSTANTS)?;
         }
+        Ok(PipelineLayout {
+            context: Arc::clone(&self.context),
+            id: Context::device_create_pipeline_layout(&*self.context, &self.id, desc)?,
+        })
     }
 
     /// Creates a [`RenderPipeline`].
+    ///
+    /// # Panics
+    ///
+    /// - The descriptor fails validation, after the error reached an error scope or the
+    ///   handler set with [`Device::on_uncaptured_error`]. Use
+    ///   [`Device::try_create_render_pipeline`] to handle the error instead.
     pub fn create_render_pipeline(&self, desc: &RenderPipelineDescriptor) -> RenderPipeline {
-        RenderPipeline {
+        self.report(
+            self.create_render_pipeline_impl(desc),
+            "Device::create_render_pipeline",
+        )
+    }
+
+    /// Creates a [`RenderPipeline`], returning an error if the descriptor is invalid.
+    ///
+    /// This is the entry point to use when shaders or pipeline state come from
+    /// untrusted or user-authored sources.
+    pub fn try_create_render_pipeline(
+        &self,
+        desc: &RenderPipelineDescriptor,
+    ) -> Result<RenderPipeline, CreateError> {
+        self.capture(self.create_render_pipeline_impl(desc))
+    }
+
+    fn create_render_pipeline_impl(
+        &self,
+        desc: &RenderPipelineDescriptor,
+    ) -> Result<RenderPipeline, CreateError> {
+        Ok(RenderPipeline {
             context: Arc::clone(&self.context),
-            id: Context::device_create_render_pipeline(&*self.context, &self.id, desc),
-        }
+            id: Context::device_create_render_pipeline(&*self.context, &self.id, desc)?,
+        })
     }
 
     /// Creates a [`ComputePipeline`].
+    ///
+    /// # Panics
+    ///
+    /// - The descriptor fails validation, after the error reached an error scope or the
+    ///   handler set with [`Device::on_uncaptured_error`]. Use
+    ///   [`Device::try_create_compute_pipeline`] to handle the error instead.
     pub fn create_compute_pipeline(&self, desc: &ComputePipelineDescriptor) -> ComputePipeline {
-        ComputePipeline {
+        self.report(
+            self.create_compute_pipeline_impl(desc),
+            "Device::create_compute_pipeline",
+        )
+    }
+
+    /// Creates a [`ComputePipeline`], returning an error if the descriptor is invalid.
+    pub fn try_create_compute_pipeline(
+        &self,
+        desc: &ComputePipelineDescriptor,
+    ) -> Result<ComputePipeline, CreateError> {
+        self.capture(self.create_compute_pipeline_impl(desc))
+    }
+
+    fn create_compute_pipeline_impl(
+        &self,
+        desc: &ComputePipelineDescriptor,
+    ) -> Result<ComputePipeline, CreateError> {
+        Ok(ComputePipeline {
             context: Arc::clone(&self.context),
-            id: Context::device_create_compute_pipeline(&*self.context, &self.id, desc),
-        }
+            id: Context::device_create_compute_pipeline(&*self.context, &self.id, desc)?,
+        })
     }
 
     /// Creates a [`Buffer`].
+    ///
+    /// # Panics
+    ///
+    /// - The descriptor fails validation, after the error reached an error scope or the
+    ///   handler set with [`Device::on_uncaptured_error`]. Use
+    ///   [`Device::try_create_buffer`] to handle the error instead.
     pub fn create_buffer(&self, desc: &BufferDescriptor) -> Buffer {
+        self.report(self.create_buffer_impl(desc), "Device::create_buffer")
+    }
+
+    /// Creates a [`Buffer`], returning an error if the descriptor is invalid.
+    pub fn try_create_buffer(&self, desc: &BufferDescriptor) -> Result<Buffer, CreateError> {
+        self.capture(self.create_buffer_impl(desc))
+    }
+
+    fn create_buffer_impl(&self, desc: &BufferDescriptor) -> Result<Buffer, CreateError> {
+        validation::validate_buffer(desc)?;
         let mut map_context = MapContext::new(desc.size);
         if desc.mapped_at_creation {
             map_context.initial_range = 0..desc.size;
         }
-        Buffer {
+        Ok(Buffer {
             context: Arc::clone(&self.context),
-            id: Context::device_create_buffer(&*self.context, &self.id, desc),
-            map_context: Mutex::new(map_context),
+            id: Context::device_create_buffer(&*self.context, &self.id, desc)?,
+            map_context: Arc::new(Mutex::new(map_context)),
             usage: desc.usage,
-        }
+        })
     }
 
     /// Creates a new buffer, maps it into host-visible memory, copies data from the given slice,
@@ -1210,22 +1490,58 @@ impl Device {
     /// Creates a new [`Texture`].
     ///
     /// `desc` specifies the general format of the texture.
+    ///
+    /// # Panics
+    ///
+    /// - The descriptor fails validation, after the error reached an error scope or the
+    ///   handler set with [`Device::on_uncaptured_error`]. Use
+    ///   [`Device::try_create_texture`] to handle the error instead.
     pub fn create_texture(&self, desc: &TextureDescriptor) -> Texture {
-        Texture {
+        self.report(self.create_texture_impl(desc), "Device::create_texture")
+    }
+
+    /// Creates a new [`Texture`], returning an error if the descriptor is invalid.
+    pub fn try_create_texture(&self, desc: &TextureDescriptor) -> Result<Texture, CreateError> {
+        self.capture(self.create_texture_impl(desc))
+    }
+
+    fn create_texture_impl(&self, desc: &TextureDescriptor) -> Result<Texture, CreateError> {
+        validation::validate_texture(desc)?;
+        Ok(Texture {
             context: Arc::clone(&self.context),
-            id: Context::device_create_texture(&*self.context, &self.id, desc),
+            id: Context::device_create_texture(&*self.context, &self.id, desc)?,
             owned: true,
-        }
+            size: desc.size,
+            mip_level_count: desc.mip_level_count,
+            dimension: desc.dimension,
+            format: desc.format,
+        })
     }
 
     /// Creates a new [`Sampler`].
     ///
     /// `desc` specifies the behavior of the sampler.
+    ///
+    /// # Panics
+    ///
+    /// - The descriptor fails validation, after the error reached an error scope or the
+    ///   handler set with [`Device::on_uncaptured_error`]. Use
+    ///   [`Device::try_create_sampler`] to handle the error instead.
     pub fn create_sampler(&self, desc: &SamplerDescriptor) -> Sampler {
-        Sampler {
+        self.report(self.create_sampler_impl(desc), "Device::create_sampler")
+    }
+
+    /// Creates a new [`Sampler`], returning an error if the descriptor is invalid.
+    pub fn try_create_sampler(&self, desc: &SamplerDescriptor) -> Result<Sampler, CreateError> {
+        self.capture(self.create_sampler_impl(desc))
+    }
+
+    fn create_sampler_impl(&self, desc: &SamplerDescriptor) -> Result<Sampler, CreateError> {
+        validation::validate_sampler(desc)?;
+        Ok(Sampler {
             context: Arc::clone(&self.context),
-            id: Context::device_create_sampler(&*self.context, &self.id, desc),
-        }
+            id: Context::device_create_sampler(&*self.context, &self.id, desc)?,
+        })
     }
 
     /// Create a new [`SwapChain`] which targets `surface`.
@@ -1240,6 +1556,72 @@ impl Device {
             id: Context::device_create_swap_chain(&*self.context, &self.id, &surface.id, desc),
         }
     }
+
+    /// Pushes a new error scope onto the device's error scope stack.
+    ///
+    /// Until the matching [`Device::pop_error_scope`], errors matching `filter` are captured
+    /// by this scope instead of being reported as uncaptured. Only the first error captured
+    /// by a scope is kept.
+    pub fn push_error_scope(&self, filter: ErrorFilter) {
+        Context::device_push_error_scope(&*self.context, &self.id, filter);
+    }
+
+    /// Pops the innermost error scope, returning a future that resolves to the first error
+    /// it captured, if any.
+    ///
+    /// On the web, the browser keeps the scopes and captures its own errors, while the errors
+    /// of checks done by this crate are captured by a shadow of them. If both captured an
+    /// error, the one of this crate is returned.
+    ///
+    /// # Panics
+    ///
+    /// - There is no error scope to pop.
+    pub fn pop_error_scope(&self) -> impl Future<Output = Option<Error>> + Send {
+        Context::device_pop_error_scope(&*self.context, &self.id)
+    }
+
+    /// Sets the callback invoked for errors that are not captured by any error scope.
+    ///
+    /// This replaces the previous handler. By default, uncaptured errors are emitted as a
+    /// `tracing` event and then cause a panic. The handler is called on the thread that
+    /// issued the failing operation, so it runs inside whatever `tracing` spans are active
+    /// there. It may use the device, including its error scopes.
+    ///
+    /// Errors of the `create_*` methods reach the handler too. Those methods have no object
+    /// to return in place of the resource, so they still panic if the handler returns.
+    pub fn on_uncaptured_error(&self, handler: Box<dyn Fn(Error) + Send + Sync>) {
+        Context::device_on_uncaptured_error(&*self.context, &self.id, handler);
+    }
+
+    fn require_features(&self, features: Features) -> Result<(), CreateError> {
+        let missing = features - self.features();
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(CreateError::UnsupportedFeature(missing))
+        }
+    }
+
+    /// Records a creation error in the error scopes of the device, and hands it back
+    /// to the caller of a `try_create_*` method.
+    fn capture<T>(&self, result: Result<T, CreateError>) -> Result<T, CreateError> {
+        if let Err(ref error) = result {
+            Context::device_capture_error(&*self.context, &self.id, error.clone().into());
+        }
+        result
+    }
+
+    /// Captures a creation error in the error scopes of the device, or passes it to the
+    /// uncaptured error handler.
+    ///
+    /// There is no invalid object to return in place of the resource, so if the handler
+    /// returns, or a scope captured the error, `call` panics after all.
+    fn report<T>(&self, result: Result<T, CreateError>, call: &str) -> T {
+        result.unwrap_or_else(|error| {
+            Context::device_report_error(&*self.context, &self.id, error.clone().into());
+            panic!("{} failed: {}", call, error)
+        })
+    }
 }
 
 impl Drop for Device {
@@ -1254,10 +1636,135 @@ impl Drop for Device {
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct RequestDeviceError;
 
+/// Error returned by the `Device::try_create_*` family of methods.
+///
+/// Not every invalid descriptor is caught: the checks of this crate and of the backend cover
+/// what they can express, and the rest still panics in the backend. In particular, SPIR-V
+/// modules are only checked for their header and the lengths of their instructions, so a
+/// module with invalid instructions reaches the backend. The native one panics on it when
+/// creating the module or a pipeline using it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CreateError {
+    /// The descriptor or the shader source failed validation.
+    Validation(String),
+    /// There is not enough memory left to allocate the resource.
+    ///
+    /// The native backend never returns it, as wgpu-core panics on allocation failures.
+    OutOfMemory,
+    /// The descriptor requires features that were not enabled on the device.
+    UnsupportedFeature(Features),
+}
+
+impl std::fmt::Display for CreateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            CreateError::Validation(ref description) => {
+                write!(f, "validation error: {}", description)
+            }
+            CreateError::OutOfMemory => write!(f, "out of memory"),
+            CreateError::UnsupportedFeature(features) => {
+                write!(f, "features {:?} are not enabled on the device", features)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CreateError {}
+
+/// Kind of errors captured by an error scope.
+///
+/// Passed to [`Device::push_error_scope`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorFilter {
+    /// Catch only out-of-memory errors.
+    OutOfMemory,
+    /// Catch only validation errors.
+    Validation,
+}
+
+/// Error reported by the device, as captured by an error scope.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Error {
+    /// There is not enough memory left to complete the operation.
+    OutOfMemory,
+    /// An operation was invalid.
+    Validation {
+        /// Description of the validation failure.
+        description: String,
+    },
+}
+
+impl Error {
+    /// Returns the filter that captures this error.
+    pub fn filter(&self) -> ErrorFilter {
+        match *self {
+            Error::OutOfMemory => ErrorFilter::OutOfMemory,
+            Error::Validation { .. } => ErrorFilter::Validation,
+        }
+    }
+}
+
+impl From<CreateError> for Error {
+    fn from(error: CreateError) -> Self {
+        match error {
+            CreateError::Validation(description) => Error::Validation { description },
+            CreateError::OutOfMemory => Error::OutOfMemory,
+            other @ CreateError::UnsupportedFeature(_) => Error::Validation {
+                description: other.to_string(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            Error::OutOfMemory => write!(f, "out of memory"),
+            Error::Validation { ref description } => {
+                write!(f, "validation error: {}", description)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
 /// Error occurred when trying to async map a number.
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct BufferAsyncError;
 
+/// Error returned by the `try_` methods accessing the mapped memory of a [`Buffer`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum MapError {
+    /// The buffer is not mapped, or a [`BufferSlice::map_async`] has not resolved yet.
+    NotMapped,
+    /// The slice is not within the mapped range of the buffer.
+    OutOfRange(Range<BufferAddress>),
+    /// The slice intersects the range of a live view it can't share memory with.
+    ///
+    /// Read-only views may overlap each other, but writable views are exclusive.
+    Overlap(Range<BufferAddress>),
+    /// The buffer can't be unmapped while views of its mapped memory are alive.
+    ViewsAlive,
+}
+
+impl std::fmt::Display for MapError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            MapError::NotMapped => write!(f, "buffer is not mapped"),
+            MapError::OutOfRange(ref range) => {
+                write!(f, "range {:?} is outside of the mapped range", range)
+            }
+            MapError::Overlap(ref range) => {
+                write!(f, "slice overlaps the mapped view of range {:?}", range)
+            }
+            MapError::ViewsAlive => write!(f, "mapped views of the buffer are still alive"),
+        }
+    }
+}
+
+impl std::error::Error for MapError {}
+
 /// Type of buffer mapping.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MapMode {
@@ -1330,7 +1837,7 @@ impl Drop for BufferView<'_> {
             .buffer
             .map_context
             .lock()
-            .remove(self.slice.offset, self.slice.size);
+            .remove(self.slice.offset, self.slice.size, false);
     }
 }
 
@@ -1340,7 +1847,7 @@ impl Drop for BufferViewMut<'_> {
             .buffer
             .map_context
             .lock()
-            .remove(self.slice.offset, self.slice.size);
+            .remove(self.slice.offset, self.slice.size, true);
     }
 }
 
@@ -1357,9 +1864,26 @@ impl Buffer {
     }
 
     /// Flushes any pending write operations and unmaps the buffer from host memory.
+    ///
+    /// A pending [`BufferSlice::map_async`] is cancelled, and its future resolves to an error.
+    ///
+    /// # Panics
+    ///
+    /// - Views of the mapped memory are still alive. Use [`Buffer::try_unmap`] to handle
+    ///   the error instead.
     pub fn unmap(&self) {
-        self.map_context.lock().reset();
+        self.try_unmap()
+            .unwrap_or_else(|err| panic!("Buffer::unmap failed: {}", err))
+    }
+
+    /// Flushes any pending write operations and unmaps the buffer from host memory,
+    /// failing if views of the mapped memory are still alive.
+    ///
+    /// A pending [`BufferSlice::map_async`] is cancelled, and its future resolves to an error.
+    pub fn try_unmap(&self) -> Result<(), MapError> {
+        self.map_context.lock().reset()?;
         Context::buffer_unmap(&*self.context, &self.id);
+        Ok(())
     }
 }
 
@@ -1374,11 +1898,15 @@ impl<'a> BufferSlice<'a> {
     ///
     /// It's expected that wgpu will eventually supply its own event loop infrastructure that will be easy to integrate
     /// into other event loops, like winit's.
+    ///
+    /// The mapped memory can be accessed once the future resolved. Unmapping the buffer
+    /// before that cancels the map, and dropping the future abandons it, so the buffer can
+    /// be mapped again either way.
     pub fn map_async(
         &self,
         mode: MapMode,
     ) -> impl Future<Output = Result<(), BufferAsyncError>> + Send {
-        let end = {
+        let (end, generation, abandoned) = {
             let mut mc = self.buffer.map_context.lock();
             assert_eq!(
                 mc.initial_range,
@@ -1391,7 +1919,19 @@ impl<'a> BufferSlice<'a> {
                 None => mc.total_size,
             };
             mc.initial_range = self.offset..end;
-            end
+            mc.pending = true;
+            mc.generation += 1;
+            let abandoned = mem::replace(&mut mc.abandoned, false);
+            (end, mc.generation, abandoned)
+        };
+        if abandoned {
+            // The backend may have mapped the buffer for the abandoned future.
+            Context::buffer_unmap(&*self.buffer.context, &self.buffer.id);
+        }
+        let mut pending = PendingMap {
+            map_context: Arc::clone(&self.buffer.map_context),
+            generation,
+            resolved: false,
         };
         Context::buffer_map_async(
             &*self.buffer.context,
@@ -1399,34 +1939,70 @@ impl<'a> BufferSlice<'a> {
             mode,
             self.offset..end,
         )
+        .map(move |result| {
+            pending.resolved = true;
+            pending
+                .map_context
+                .lock()
+                .resolve(pending.generation, result)
+        })
     }
 
     /// Synchronously and immediately map a buffer for reading. If the buffer is not immediately mappable
     /// through [`BufferDescriptor::mapped_at_creation`] or [`BufferSlice::map_async`], will panic.
+    ///
+    /// Read-only views may overlap each other, but not a view returned by
+    /// [`BufferSlice::get_mapped_range_mut`]. Use [`BufferSlice::try_get_mapped_range`] to handle
+    /// errors instead of panicking.
     pub fn get_mapped_range(&self) -> BufferView<'a> {
-        let end = self.buffer.map_context.lock().add(self.offset, self.size);
+        self.try_get_mapped_range()
+            .unwrap_or_else(|err| panic!("BufferSlice::get_mapped_range failed: {}", err))
+    }
+
+    /// Synchronously and immediately map a buffer for reading, failing if the buffer is not
+    /// mapped yet, the slice is out of the mapped range or it overlaps a writable view.
+    pub fn try_get_mapped_range(&self) -> Result<BufferView<'a>, MapError> {
+        let end = self
+            .buffer
+            .map_context
+            .lock()
+            .add(self.offset, self.size, false)?;
         let data = Context::buffer_get_mapped_range(
             &*self.buffer.context,
             &self.buffer.id,
             self.offset..end,
         );
-        BufferView { slice: *self, data }
+        Ok(BufferView { slice: *self, data })
     }
 
     /// Synchronously and immediately map a buffer for writing. If the buffer is not immediately mappable
     /// through [`BufferDescriptor::mapped_at_creation`] or [`BufferSlice::map_async`], will panic.
+    ///
+    /// The view may not overlap any other view. Use [`BufferSlice::try_get_mapped_range_mut`] to
+    /// handle errors instead of panicking.
     pub fn get_mapped_range_mut(&self) -> BufferViewMut<'a> {
-        let end = self.buffer.map_context.lock().add(self.offset, self.size);
+        self.try_get_mapped_range_mut()
+            .unwrap_or_else(|err| panic!("BufferSlice::get_mapped_range_mut failed: {}", err))
+    }
+
+    /// Synchronously and immediately map a buffer for writing, failing if the buffer is not
+    /// mapped yet, the slice is out of the mapped range or it overlaps another view.
+    pub fn try_get_mapped_range_mut(&self) -> Result<BufferViewMut<'a>, MapError> {
+        let end = self
+            .buffer
+            .map_context
+            .lock()
+            .add(self.offset, self.size, true)?;
         let data = Context::buffer_get_mapped_range_mut(
             &*self.buffer.context,
             &self.buffer.id,
             self.offset..end,
         );
-        BufferViewMut {
+        Ok(BufferViewMut {
             slice: *self,
             data,
             readable: self.buffer.usage.contains(BufferUsage::MAP_READ),
-        }
+        })
     }
 }
 
@@ -1764,9 +2340,13 @@ impl<'a> RenderPass<'a> {
     }
 }
 
-/// [`Features::MULTI_DRAW_INDIRECT`] must be enabled on the device in order to call these functions.
+/// On the native backend, [`Features::MULTI_DRAW_INDIRECT`] must be enabled on the device in order
+/// to call these functions.
+///
+/// The web backend never reports the feature, and runs these functions as a loop of single
+/// indirect draws instead, so they can be called without it there.
 impl<'a> RenderPass<'a> {
-    /// Disptaches multiple draw calls from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
+    /// Dispatches multiple draw calls from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
     /// `count` draw calls are issued.
     ///
     /// The active vertex buffers can be set with [`RenderPass::set_vertex_buffer`].
@@ -1794,7 +2374,7 @@ impl<'a> RenderPass<'a> {
             .multi_draw_indirect(&indirect_buffer.id, indirect_offset, count);
     }
 
-    /// Disptaches multiple draw calls from the active index buffer and the active vertex buffers,
+    /// Dispatches multiple draw calls from the active index buffer and the active vertex buffers,
     /// based on the contents of the `indirect_buffer`. `count` draw calls are issued.
     ///
     /// The active index buffer can be set with [`RenderPass::set_index_buffer`], while the active
@@ -1826,8 +2406,11 @@ impl<'a> RenderPass<'a> {
 }
 
 /// [`Features::MULTI_DRAW_INDIRECT_COUNT`] must be enabled on the device in order to call these functions.
+///
+/// The web backend never reports the feature: the draw count is only known to the GPU, so these
+/// functions can't be emulated with single draws, and panic there.
 impl<'a> RenderPass<'a> {
-    /// Disptaches multiple draw calls from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
+    /// Dispatches multiple draw calls from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
     /// The count buffer is read to determine how many draws to issue.
     ///
     /// The indirect buffer must be long enough to account for `max_count` draws, however only `count` will
@@ -1857,6 +2440,10 @@ impl<'a> RenderPass<'a> {
     ///     count: u32, // Number of draw calls to issue.
     /// }
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Called on the web backend.
     pub fn multi_draw_indirect_count(
         &mut self,
         indirect_buffer: &'a Buffer,
@@ -1874,7 +2461,7 @@ impl<'a> RenderPass<'a> {
         );
     }
 
-    /// Disptaches multiple draw calls from the active index buffer and the active vertex buffers,
+    /// Dispatches multiple draw calls from the active index buffer and the active vertex buffers,
     /// based on the contents of the `indirect_buffer`. The count buffer is read to determine how many draws to issue.
     ///
     /// The indirect buffer must be long enough to account for `max_count` draws, however only `count` will
@@ -1906,6 +2493,10 @@ impl<'a> RenderPass<'a> {
     ///     count: u32, // Number of draw calls to issue.
     /// }
     /// ```
+    ///
+    /// # Panics
+    ///
+    /// - Called on the web backend.
     pub fn multi_draw_indexed_indirect_count(
         &mut self,
         indirect_buffer: &'a Buffer,
@@ -2153,6 +2744,68 @@ impl<'a> RenderBundleEncoder<'a> {
     }
 }
 
+/// Render bundles have no native multi-draw support, so these are emulated with a loop of single
+/// indirect draws on all backends.
+impl<'a> RenderBundleEncoder<'a> {
+    /// Dispatches multiple draw calls from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
+    /// `count` draw calls are issued.
+    ///
+    /// The active vertex buffers can be set with [`RenderBundleEncoder::set_vertex_buffer`].
+    ///
+    /// The structure expected in `indirect_buffer` is the following:
+    ///
+    /// ```rust
+    /// #[repr(C)]
+    /// struct DrawIndirect {
+    ///     vertex_count: u32, // The number of vertices to draw.
+    ///     instance_count: u32, // The number of instances to draw.
+    ///     base_vertex: u32, // The Index of the first vertex to draw.
+    ///     base_instance: u32, // The instance ID of the first instance to draw.
+    /// }
+    /// ```
+    ///
+    /// These draw structures are expected to be tightly packed.
+    pub fn multi_draw_indirect(
+        &mut self,
+        indirect_buffer: &'a Buffer,
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        self.id
+            .multi_draw_indirect(&indirect_buffer.id, indirect_offset, count);
+    }
+
+    /// Dispatches multiple draw calls from the active index buffer and the active vertex buffers,
+    /// based on the contents of the `indirect_buffer`. `count` draw calls are issued.
+    ///
+    /// The active index buffer can be set with [`RenderBundleEncoder::set_index_buffer`], while the active
+    /// vertex buffers can be set with [`RenderBundleEncoder::set_vertex_buffer`].
+    ///
+    /// The structure expected in `indirect_buffer` is the following:
+    ///
+    /// ```rust
+    /// #[repr(C)]
+    /// struct DrawIndexedIndirect {
+    ///     vertex_count: u32, // The number of vertices to draw.
+    ///     instance_count: u32, // The number of instances to draw.
+    ///     base_index: u32, // The base index within the index buffer.
+    ///     vertex_offset: i32, // The value added to the vertex index before indexing into the vertex buffer.
+    ///     base_instance: u32, // The instance ID of the first instance to draw.
+    /// }
+    /// ```
+    ///
+    /// These draw structures are expected to be tightly packed.
+    pub fn multi_draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: &'a Buffer,
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        self.id
+            .multi_draw_indexed_indirect(&indirect_buffer.id, indirect_offset, count);
+    }
+}
+
 /// [`Features::PUSH_CONSTANTS`] must be enabled on the device in order to call these functions.
 impl<'a> RenderBundleEncoder<'a> {
     /// Set push constant data.
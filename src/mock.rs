@@ -0,0 +1,403 @@
+//! In-memory backend for testing without a GPU.
+//!
+//! Enabled with the `mock` feature, next to the native and web backends: an [`Instance`] created
+//! with [`Instance::new_mock`] and everything created from it use this backend. Buffers and
+//! textures are plain host memory: queue writes, copies and buffer mapping all work on real bytes,
+//! honoring [`TextureDataLayout`](crate::TextureDataLayout), origins, array layers and mip levels.
+//! Everything submitted to a [`Queue`] is also recorded into a [`Command`] log that can be
+//! inspected with [`Queue::command_log`]. Invalid copies are reported as validation errors.
+//!
+//! Resources are referred to by [`ResourceId`], obtained through the [`Resource`] trait.
+
+use crate::{
+    BindGroup, Buffer, BufferAddress, BufferSize, Color, ComputePipeline, Device, DynamicOffset,
+    Extent3d, Instance, Queue, RenderBundle, RenderPipeline, Sampler, ShaderStage, Texture,
+    TextureView,
+};
+use std::{ops::Range, sync::Arc};
+
+/// Kind of resource a [`ResourceId`] refers to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceKind {
+    /// A [`Buffer`].
+    Buffer,
+    /// A [`Texture`].
+    Texture,
+    /// A [`TextureView`].
+    TextureView,
+    /// A [`Sampler`].
+    Sampler,
+    /// A [`BindGroupLayout`](crate::BindGroupLayout).
+    BindGroupLayout,
+    /// A [`BindGroup`].
+    BindGroup,
+    /// A [`ShaderModule`](crate::ShaderModule).
+    ShaderModule,
+    /// A [`PipelineLayout`](crate::PipelineLayout).
+    PipelineLayout,
+    /// A [`RenderPipeline`].
+    RenderPipeline,
+    /// A [`ComputePipeline`].
+    ComputePipeline,
+    /// A [`RenderBundle`].
+    RenderBundle,
+}
+
+/// Unique identifier of a resource created by the mock backend.
+///
+/// Identifiers are never reused within an [`Instance`](crate::Instance).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct ResourceId {
+    pub(crate) kind: ResourceKind,
+    pub(crate) index: u64,
+}
+
+impl ResourceId {
+    /// Kind of the resource.
+    pub fn kind(&self) -> ResourceKind {
+        self.kind
+    }
+}
+
+/// Operation executed by a [`Queue`], in submission order.
+///
+/// Offsets and sizes are in bytes, as passed to the corresponding wgpu call.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    /// [`Queue::write_buffer`].
+    WriteBuffer {
+        /// Buffer written to.
+        buffer: ResourceId,
+        /// Offset of the write.
+        offset: BufferAddress,
+        /// Size of the write.
+        size: BufferAddress,
+    },
+    /// [`Queue::write_texture`].
+    WriteTexture {
+        /// Texture written to.
+        texture: ResourceId,
+        /// Extent of the write.
+        size: Extent3d,
+    },
+    /// [`CommandEncoder::copy_buffer_to_buffer`](crate::CommandEncoder::copy_buffer_to_buffer).
+    CopyBufferToBuffer {
+        /// Buffer copied from.
+        source: ResourceId,
+        /// Offset into the source buffer.
+        source_offset: BufferAddress,
+        /// Buffer copied to.
+        destination: ResourceId,
+        /// Offset into the destination buffer.
+        destination_offset: BufferAddress,
+        /// Size of the copy.
+        size: BufferAddress,
+    },
+    /// [`CommandEncoder::copy_buffer_to_texture`](crate::CommandEncoder::copy_buffer_to_texture).
+    CopyBufferToTexture {
+        /// Buffer copied from.
+        source: ResourceId,
+        /// Texture copied to.
+        destination: ResourceId,
+        /// Extent of the copy.
+        size: Extent3d,
+    },
+    /// [`CommandEncoder::copy_texture_to_buffer`](crate::CommandEncoder::copy_texture_to_buffer).
+    CopyTextureToBuffer {
+        /// Texture copied from.
+        source: ResourceId,
+        /// Buffer copied to.
+        destination: ResourceId,
+        /// Extent of the copy.
+        size: Extent3d,
+    },
+    /// [`CommandEncoder::copy_texture_to_texture`](crate::CommandEncoder::copy_texture_to_texture).
+    CopyTextureToTexture {
+        /// Texture copied from.
+        source: ResourceId,
+        /// Texture copied to.
+        destination: ResourceId,
+        /// Extent of the copy.
+        size: Extent3d,
+    },
+    /// Start of a compute pass.
+    BeginComputePass,
+    /// End of a compute pass.
+    EndComputePass,
+    /// Start of a render pass.
+    BeginRenderPass {
+        /// Views of the color attachments.
+        color_attachments: Vec<ResourceId>,
+        /// View of the depth/stencil attachment.
+        depth_stencil_attachment: Option<ResourceId>,
+    },
+    /// End of a render pass.
+    EndRenderPass,
+    /// A render or compute pipeline was set.
+    SetPipeline(ResourceId),
+    /// A bind group was set.
+    SetBindGroup {
+        /// Index the bind group was set at.
+        index: u32,
+        /// The bind group.
+        bind_group: ResourceId,
+        /// Dynamic offsets.
+        offsets: Vec<DynamicOffset>,
+    },
+    /// The index buffer was set.
+    SetIndexBuffer {
+        /// The index buffer.
+        buffer: ResourceId,
+        /// Offset into the buffer.
+        offset: BufferAddress,
+        /// Size of the bound range, or `None` for the rest of the buffer.
+        size: Option<BufferSize>,
+    },
+    /// A vertex buffer was set.
+    SetVertexBuffer {
+        /// Slot the buffer was set at.
+        slot: u32,
+        /// The vertex buffer.
+        buffer: ResourceId,
+        /// Offset into the buffer.
+        offset: BufferAddress,
+        /// Size of the bound range, or `None` for the rest of the buffer.
+        size: Option<BufferSize>,
+    },
+    /// Push constants were set.
+    SetPushConstants {
+        /// Stages the constants are visible to.
+        stages: ShaderStage,
+        /// Offset of the constants.
+        offset: u32,
+        /// The constants.
+        data: Vec<u32>,
+    },
+    /// [`RenderPass::set_blend_color`](crate::RenderPass::set_blend_color).
+    SetBlendColor(Color),
+    /// [`RenderPass::set_scissor_rect`](crate::RenderPass::set_scissor_rect).
+    SetScissorRect {
+        /// Left edge.
+        x: u32,
+        /// Top edge.
+        y: u32,
+        /// Width.
+        width: u32,
+        /// Height.
+        height: u32,
+    },
+    /// [`RenderPass::set_viewport`](crate::RenderPass::set_viewport).
+    SetViewport {
+        /// Left edge.
+        x: f32,
+        /// Top edge.
+        y: f32,
+        /// Width.
+        width: f32,
+        /// Height.
+        height: f32,
+        /// Minimum depth.
+        min_depth: f32,
+        /// Maximum depth.
+        max_depth: f32,
+    },
+    /// [`RenderPass::set_stencil_reference`](crate::RenderPass::set_stencil_reference).
+    SetStencilReference(u32),
+    /// A non-indexed draw.
+    Draw {
+        /// Range of vertices.
+        vertices: Range<u32>,
+        /// Range of instances.
+        instances: Range<u32>,
+    },
+    /// An indexed draw.
+    DrawIndexed {
+        /// Range of indices.
+        indices: Range<u32>,
+        /// Value added to each index.
+        base_vertex: i32,
+        /// Range of instances.
+        instances: Range<u32>,
+    },
+    /// A non-indexed indirect draw.
+    DrawIndirect {
+        /// Buffer holding the arguments.
+        buffer: ResourceId,
+        /// Offset of the arguments.
+        offset: BufferAddress,
+    },
+    /// An indexed indirect draw.
+    DrawIndexedIndirect {
+        /// Buffer holding the arguments.
+        buffer: ResourceId,
+        /// Offset of the arguments.
+        offset: BufferAddress,
+    },
+    /// Several non-indexed indirect draws.
+    MultiDrawIndirect {
+        /// Buffer holding the arguments.
+        buffer: ResourceId,
+        /// Offset of the first draw's arguments.
+        offset: BufferAddress,
+        /// Number of draws.
+        count: u32,
+    },
+    /// Several indexed indirect draws.
+    MultiDrawIndexedIndirect {
+        /// Buffer holding the arguments.
+        buffer: ResourceId,
+        /// Offset of the first draw's arguments.
+        offset: BufferAddress,
+        /// Number of draws.
+        count: u32,
+    },
+    /// Several non-indexed indirect draws, with the count read from a buffer.
+    MultiDrawIndirectCount {
+        /// Buffer holding the arguments.
+        buffer: ResourceId,
+        /// Offset of the first draw's arguments.
+        offset: BufferAddress,
+        /// Buffer holding the draw count.
+        count_buffer: ResourceId,
+        /// Offset of the draw count.
+        count_offset: BufferAddress,
+        /// Maximum number of draws.
+        max_count: u32,
+    },
+    /// Several indexed indirect draws, with the count read from a buffer.
+    MultiDrawIndexedIndirectCount {
+        /// Buffer holding the arguments.
+        buffer: ResourceId,
+        /// Offset of the first draw's arguments.
+        offset: BufferAddress,
+        /// Buffer holding the draw count.
+        count_buffer: ResourceId,
+        /// Offset of the draw count.
+        count_offset: BufferAddress,
+        /// Maximum number of draws.
+        max_count: u32,
+    },
+    /// A compute dispatch.
+    Dispatch {
+        /// Number of work groups in X.
+        x: u32,
+        /// Number of work groups in Y.
+        y: u32,
+        /// Number of work groups in Z.
+        z: u32,
+    },
+    /// An indirect compute dispatch.
+    DispatchIndirect {
+        /// Buffer holding the arguments.
+        buffer: ResourceId,
+        /// Offset of the arguments.
+        offset: BufferAddress,
+    },
+    /// A debug marker was inserted.
+    InsertDebugMarker(String),
+    /// A debug group was pushed.
+    PushDebugGroup(String),
+    /// A debug group was popped.
+    PopDebugGroup,
+    /// A render bundle was executed. Its commands follow in the log.
+    ExecuteBundle(ResourceId),
+    /// A swap chain texture was presented.
+    Present(ResourceId),
+}
+
+/// Resource that has a [`ResourceId`] in the mock backend.
+pub trait Resource {
+    /// Identifier of this resource.
+    ///
+    /// # Panics
+    ///
+    /// - The resource was not created from an [`Instance::new_mock`].
+    fn mock_id(&self) -> ResourceId;
+}
+
+macro_rules! impl_resource {
+    ($($ty:ident),*) => {
+        $(
+            impl Resource for $ty {
+                fn mock_id(&self) -> ResourceId {
+                    self.id.mock().id
+                }
+            }
+        )*
+    };
+}
+
+impl_resource!(
+    Buffer,
+    Texture,
+    TextureView,
+    Sampler,
+    BindGroup,
+    RenderPipeline,
+    ComputePipeline,
+    RenderBundle
+);
+
+impl Instance {
+    /// Create an instance of the mock backend.
+    ///
+    /// It has a single adapter, which supports all features and the default limits.
+    pub fn new_mock() -> Self {
+        Instance {
+            context: Arc::new(crate::backend::Context::new_mock()),
+        }
+    }
+}
+
+impl Queue {
+    /// Returns all commands executed by this queue so far.
+    ///
+    /// # Panics
+    ///
+    /// - The queue was not created from an [`Instance::new_mock`].
+    pub fn command_log(&self) -> Vec<Command> {
+        self.id.mock().commands.lock().clone()
+    }
+
+    /// Returns all commands executed by this queue so far and clears the log.
+    ///
+    /// # Panics
+    ///
+    /// - The queue was not created from an [`Instance::new_mock`].
+    pub fn take_command_log(&self) -> Vec<Command> {
+        std::mem::replace(&mut *self.id.mock().commands.lock(), Vec::new())
+    }
+}
+
+impl Device {
+    /// Returns the identifiers of all resources that have been created and not dropped yet.
+    ///
+    /// # Panics
+    ///
+    /// - The device was not created from an [`Instance::new_mock`].
+    pub fn live_resources(&self) -> Vec<ResourceId> {
+        self.context.mock().live_resources()
+    }
+}
+
+/// Requests the device and queue of a mock instance, for the tests of the crate.
+#[cfg(test)]
+pub(crate) fn request_device() -> (Device, Queue) {
+    let instance = Instance::new_mock();
+    let adapter =
+        futures::executor::block_on(instance.request_adapter(&crate::RequestAdapterOptions {
+            power_preference: crate::PowerPreference::Default,
+            compatible_surface: None,
+        }))
+        .unwrap();
+    let (device, queue) = futures::executor::block_on(adapter.request_device(
+        &crate::DeviceDescriptor {
+            features: crate::Features::empty(),
+            limits: crate::Limits::default(),
+            shader_validation: true,
+        },
+        None,
+    ))
+    .unwrap();
+    (device, queue)
+}
@@ -0,0 +1,375 @@
+//! Tests of the device API, run against the mock backend.
+
+use crate::{
+    mock::{request_device, Command, Resource},
+    BufferDescriptor, BufferUsage, CreateError, Error, ErrorFilter, Extent3d, MapError, MapMode,
+    ShaderModuleSource, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+};
+use parking_lot::Mutex;
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::Arc,
+};
+
+#[test]
+fn try_create_rejects_invalid_descriptors() {
+    let (device, _queue) = request_device();
+
+    let shader = device.try_create_shader_module(ShaderModuleSource::SpirV(&[1, 2, 3]));
+    assert!(matches!(shader.err(), Some(CreateError::Validation(_))));
+
+    let buffer = device.try_create_buffer(&BufferDescriptor {
+        label: None,
+        size: 3,
+        usage: BufferUsage::MAP_WRITE | BufferUsage::COPY_SRC,
+        mapped_at_creation: true,
+    });
+    assert!(matches!(buffer.err(), Some(CreateError::Validation(_))));
+
+    let texture = device.try_create_texture(&TextureDescriptor {
+        label: None,
+        size: Extent3d {
+            width: 4,
+            height: 4,
+            depth: 1,
+        },
+        mip_level_count: 4,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsage::SAMPLED,
+    });
+    assert!(matches!(texture.err(), Some(CreateError::Validation(_))));
+
+    let sampler = device.try_create_sampler(&crate::SamplerDescriptor {
+        lod_min_clamp: -1.0,
+        ..Default::default()
+    });
+    assert!(matches!(sampler.err(), Some(CreateError::Validation(_))));
+}
+
+#[test]
+#[should_panic(expected = "wgpu error")]
+fn create_errors_panic_in_default_handler() {
+    let (device, _queue) = request_device();
+    device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: 4,
+        usage: BufferUsage::MAP_READ | BufferUsage::MAP_WRITE,
+        mapped_at_creation: false,
+    });
+}
+
+#[test]
+fn create_errors_reach_uncaptured_handler() {
+    let (device, _queue) = request_device();
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let handler_errors = Arc::clone(&errors);
+    device.on_uncaptured_error(Box::new(move |error: Error| {
+        handler_errors.lock().push(error)
+    }));
+
+    // There is no buffer to return once the handler returned.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: 4,
+            usage: BufferUsage::MAP_READ | BufferUsage::MAP_WRITE,
+            mapped_at_creation: false,
+        })
+    }));
+    assert!(result.is_err());
+    assert!(matches!(
+        errors.lock().as_slice(),
+        [Error::Validation { .. }]
+    ));
+}
+
+#[test]
+fn uncaptured_handler_can_use_the_device() {
+    let (device, queue) = request_device();
+    let device = Arc::new(device);
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: 4,
+        usage: BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let handler_errors = Arc::clone(&errors);
+    let handler_device = Arc::clone(&device);
+    device.on_uncaptured_error(Box::new(move |error: Error| {
+        handler_errors.lock().push(error);
+        handler_device.push_error_scope(ErrorFilter::Validation);
+    }));
+
+    queue.write_buffer(&buffer, 4, &[0; 4]);
+    assert!(matches!(
+        errors.lock().as_slice(),
+        [Error::Validation { .. }]
+    ));
+
+    // The handler pushed a scope, which captures the next error.
+    queue.write_buffer(&buffer, 4, &[0; 4]);
+    assert_eq!(errors.lock().len(), 1);
+    let captured = futures::executor::block_on(device.pop_error_scope());
+    assert!(matches!(captured, Some(Error::Validation { .. })));
+}
+
+#[test]
+fn error_scopes_capture_lib_errors() {
+    let (device, _queue) = request_device();
+    device.push_error_scope(ErrorFilter::Validation);
+    let buffer = device.try_create_buffer(&BufferDescriptor {
+        label: None,
+        size: 4,
+        usage: BufferUsage::MAP_READ | BufferUsage::MAP_WRITE,
+        mapped_at_creation: false,
+    });
+    assert!(buffer.is_err());
+    let captured = futures::executor::block_on(device.pop_error_scope());
+    assert!(matches!(captured, Some(Error::Validation { .. })));
+}
+
+#[test]
+fn error_scopes_nest_by_filter() {
+    let (device, queue) = request_device();
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: 4,
+        usage: BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let uncaptured = Arc::new(Mutex::new(0));
+    let handler_uncaptured = Arc::clone(&uncaptured);
+    device.on_uncaptured_error(Box::new(move |_: Error| *handler_uncaptured.lock() += 1));
+
+    device.push_error_scope(ErrorFilter::Validation);
+    device.push_error_scope(ErrorFilter::OutOfMemory);
+    queue.write_buffer(&buffer, 4, &[0; 4]);
+    queue.write_buffer(&buffer, 8, &[0; 4]);
+    // The inner scope doesn't match, so the outer one captures the errors.
+    assert!(futures::executor::block_on(device.pop_error_scope()).is_none());
+    let captured = futures::executor::block_on(device.pop_error_scope());
+    assert!(matches!(captured, Some(Error::Validation { .. })));
+    assert_eq!(*uncaptured.lock(), 0);
+
+    // Without a scope, errors go to the handler.
+    queue.write_buffer(&buffer, 4, &[0; 4]);
+    assert_eq!(*uncaptured.lock(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Called pop_error_scope without a matching push_error_scope")]
+fn pop_error_scope_without_push() {
+    let (device, _queue) = request_device();
+    let _ = futures::executor::block_on(device.pop_error_scope());
+}
+
+#[test]
+fn multi_draws_reach_the_backend() {
+    use crate::{
+        CommandEncoderDescriptor, LoadOp, Operations, RenderBundleDescriptor,
+        RenderBundleEncoderDescriptor, RenderPassColorAttachmentDescriptor, RenderPassDescriptor,
+    };
+
+    let (device, queue) = request_device();
+    let indirect = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: 64,
+        usage: BufferUsage::INDIRECT,
+        mapped_at_creation: false,
+    });
+    let texture = device.create_texture(&TextureDescriptor {
+        label: None,
+        size: Extent3d {
+            width: 1,
+            height: 1,
+            depth: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsage::OUTPUT_ATTACHMENT,
+    });
+    let view = texture.create_default_view();
+
+    let mut bundle_encoder = device.create_render_bundle_encoder(&RenderBundleEncoderDescriptor {
+        label: None,
+        color_formats: &[TextureFormat::Rgba8Unorm],
+        depth_stencil_format: None,
+        sample_count: 1,
+    });
+    bundle_encoder.multi_draw_indirect(&indirect, 0, 2);
+    let bundle = bundle_encoder.finish(&RenderBundleDescriptor { label: None });
+
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            color_attachments: &[RenderPassColorAttachmentDescriptor {
+                attachment: &view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        pass.execute_bundles(std::iter::once(&bundle));
+        pass.multi_draw_indirect_count(&indirect, 16, &indirect, 48, 2);
+    }
+    queue.submit(Some(encoder.finish()));
+
+    let log = queue.command_log();
+    assert!(log.contains(&Command::MultiDrawIndirect {
+        buffer: indirect.mock_id(),
+        offset: 0,
+        count: 2,
+    }));
+    assert!(log.contains(&Command::MultiDrawIndirectCount {
+        buffer: indirect.mock_id(),
+        offset: 16,
+        count_buffer: indirect.mock_id(),
+        count_offset: 48,
+        max_count: 2,
+    }));
+}
+
+#[test]
+fn overlapping_mapped_views() {
+    let (device, _queue) = request_device();
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: 16,
+        usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+        mapped_at_creation: true,
+    });
+    buffer
+        .slice(..)
+        .get_mapped_range_mut()
+        .copy_from_slice(&[0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+    {
+        // Both views are read while the other is alive.
+        let first = buffer.slice(0..8).get_mapped_range();
+        let second = buffer.slice(4..12).get_mapped_range();
+        assert_eq!(&first[4..], &second[..4]);
+        assert_eq!(&*first, &[0, 1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(&*second, &[4, 5, 6, 7, 8, 9, 10, 11]);
+        assert_eq!(
+            buffer.slice(8..16).try_get_mapped_range_mut().err(),
+            Some(MapError::Overlap(4..12))
+        );
+    }
+    {
+        // A writable view is exclusive, but disjoint views stay usable next to it.
+        let mut writable = buffer.slice(0..8).get_mapped_range_mut();
+        let readable = buffer.slice(8..16).get_mapped_range();
+        assert_eq!(
+            buffer.slice(4..8).try_get_mapped_range().err(),
+            Some(MapError::Overlap(0..8))
+        );
+        writable[0] = 42;
+        assert_eq!(readable[0], 8);
+        assert_eq!(writable[0], 42);
+    }
+    buffer.unmap();
+}
+
+#[test]
+fn map_errors() {
+    let (device, _queue) = request_device();
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: 16,
+        usage: BufferUsage::MAP_WRITE | BufferUsage::COPY_SRC,
+        mapped_at_creation: true,
+    });
+    assert_eq!(
+        buffer.slice(8..20).try_get_mapped_range_mut().err(),
+        Some(MapError::OutOfRange(8..20))
+    );
+    let view = buffer.slice(..).get_mapped_range_mut();
+    assert_eq!(buffer.try_unmap(), Err(MapError::ViewsAlive));
+    drop(view);
+    assert_eq!(buffer.try_unmap(), Ok(()));
+    assert_eq!(
+        buffer.slice(..).try_get_mapped_range().err(),
+        Some(MapError::NotMapped)
+    );
+}
+
+#[test]
+fn map_errors_while_pending() {
+    let (device, _queue) = request_device();
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: 16,
+        usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let slice = buffer.slice(..);
+    let mapping = slice.map_async(MapMode::Read);
+    assert_eq!(
+        slice.try_get_mapped_range().err(),
+        Some(MapError::NotMapped)
+    );
+    assert_eq!(
+        slice.try_get_mapped_range_mut().err(),
+        Some(MapError::NotMapped)
+    );
+
+    futures::executor::block_on(mapping).unwrap();
+    assert_eq!(slice.get_mapped_range()[..], [0; 16]);
+    assert_eq!(buffer.try_unmap(), Ok(()));
+}
+
+#[test]
+fn unmap_cancels_pending_map() {
+    let (device, _queue) = request_device();
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: 16,
+        usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let slice = buffer.slice(..);
+    let mapping = slice.map_async(MapMode::Read);
+    buffer.unmap();
+    assert!(futures::executor::block_on(mapping).is_err());
+    assert_eq!(
+        slice.try_get_mapped_range().err(),
+        Some(MapError::NotMapped)
+    );
+
+    futures::executor::block_on(slice.map_async(MapMode::Read)).unwrap();
+    assert_eq!(slice.get_mapped_range()[..], [0; 16]);
+}
+
+#[test]
+fn dropped_map_future() {
+    let (device, _queue) = request_device();
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: None,
+        size: 16,
+        usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let slice = buffer.slice(..);
+    drop(slice.map_async(MapMode::Read));
+    assert_eq!(
+        slice.try_get_mapped_range().err(),
+        Some(MapError::NotMapped)
+    );
+    buffer.unmap();
+    futures::executor::block_on(slice.map_async(MapMode::Read)).unwrap();
+    assert_eq!(slice.get_mapped_range()[..], [0; 16]);
+    buffer.unmap();
+
+    // Mapping again right away works too.
+    drop(slice.map_async(MapMode::Read));
+    futures::executor::block_on(slice.map_async(MapMode::Read)).unwrap();
+    assert_eq!(slice.get_mapped_range()[..], [0; 16]);
+}
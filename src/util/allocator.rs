@@ -0,0 +1,228 @@
+use super::align_to;
+use crate::{
+    Buffer, BufferAddress, BufferDescriptor, BufferSize, BufferSlice, BufferUsage, Device,
+    DynamicOffset,
+};
+use std::{convert::TryFrom, ops::Range};
+
+struct Block {
+    buffer: Buffer,
+    /// Unallocated ranges of the buffer, sorted and coalesced.
+    free_ranges: Vec<Range<BufferAddress>>,
+}
+
+/// An allocation made by a [`BufferAllocator`].
+///
+/// It has to be given back with [`BufferAllocator::free`] to the allocator it came from.
+#[derive(Debug, PartialEq, Eq)]
+pub struct BufferAllocation {
+    block: usize,
+    offset: BufferAddress,
+    size: BufferSize,
+}
+
+impl BufferAllocation {
+    /// Offset of the allocation in its buffer, a multiple of [`BIND_BUFFER_ALIGNMENT`](crate::BIND_BUFFER_ALIGNMENT).
+    pub fn offset(&self) -> BufferAddress {
+        self.offset
+    }
+
+    /// The offset to pass to `set_bind_group` when the buffer is bound with a dynamic offset.
+    ///
+    /// # Panics
+    ///
+    /// - The offset doesn't fit in a [`DynamicOffset`].
+    pub fn dynamic_offset(&self) -> DynamicOffset {
+        DynamicOffset::try_from(self.offset)
+            .expect("Allocation offset doesn't fit in a dynamic offset")
+    }
+
+    /// Size of the allocation, as requested.
+    pub fn size(&self) -> BufferSize {
+        self.size
+    }
+}
+
+/// Buffer allocator is a machine that carves small allocations out of large buffers.
+///
+/// Allocations start at multiples of [`BIND_BUFFER_ALIGNMENT`](crate::BIND_BUFFER_ALIGNMENT),
+/// so each can be bound on its own, or as a dynamic offset into a bind group of its buffer.
+/// Free space is tracked with a first-fit free list per buffer.
+///
+/// Buffers are never released, even once all their allocations are freed: the memory is
+/// only given back to the device when the allocator is dropped.
+pub struct BufferAllocator {
+    block_size: BufferAddress,
+    usage: BufferUsage,
+    blocks: Vec<Block>,
+}
+
+impl BufferAllocator {
+    /// Create a new buffer allocator.
+    ///
+    /// The `block_size` is the size of the buffers allocations are carved from.
+    /// Allocations larger than it get a buffer of their own.
+    pub fn new(block_size: BufferAddress, usage: BufferUsage) -> Self {
+        BufferAllocator {
+            block_size,
+            usage,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Allocate `size` bytes.
+    pub fn allocate(&mut self, size: BufferSize, device: &Device) -> BufferAllocation {
+        let aligned_size = align_to(size.get(), crate::BIND_BUFFER_ALIGNMENT);
+
+        for (index, block) in self.blocks.iter_mut().enumerate() {
+            if let Some(offset) = take_range(&mut block.free_ranges, aligned_size) {
+                return BufferAllocation {
+                    block: index,
+                    offset,
+                    size,
+                };
+            }
+        }
+
+        let block_size = self.block_size.max(aligned_size);
+        #[cfg(not(target_arch = "wasm32"))]
+        wgc::span!(
+            _guard,
+            INFO,
+            "Creating allocator block of size {}",
+            block_size
+        );
+        let mut block = Block {
+            buffer: device.create_buffer(&BufferDescriptor {
+                label: Some("allocator block"),
+                size: block_size,
+                usage: self.usage,
+                mapped_at_creation: false,
+            }),
+            free_ranges: vec![0..block_size],
+        };
+        let offset = take_range(&mut block.free_ranges, aligned_size).unwrap();
+        self.blocks.push(block);
+        BufferAllocation {
+            block: self.blocks.len() - 1,
+            offset,
+            size,
+        }
+    }
+
+    /// Give `allocation` back to the allocator.
+    ///
+    /// # Panics
+    ///
+    /// - `allocation` is already free.
+    pub fn free(&mut self, allocation: BufferAllocation) {
+        let start = allocation.offset;
+        let end = start + align_to(allocation.size.get(), crate::BIND_BUFFER_ALIGNMENT);
+        give_range(&mut self.blocks[allocation.block].free_ranges, start..end);
+    }
+
+    /// Returns the buffer `allocation` lives in.
+    pub fn buffer(&self, allocation: &BufferAllocation) -> &Buffer {
+        &self.blocks[allocation.block].buffer
+    }
+
+    /// Returns the slice of its buffer covered by `allocation`.
+    pub fn slice(&self, allocation: &BufferAllocation) -> BufferSlice {
+        self.buffer(allocation)
+            .slice(allocation.offset..allocation.offset + allocation.size.get())
+    }
+}
+
+/// Takes `size` bytes from the first free range large enough, returning their offset.
+fn take_range(
+    free_ranges: &mut Vec<Range<BufferAddress>>,
+    size: BufferAddress,
+) -> Option<BufferAddress> {
+    let index = free_ranges
+        .iter()
+        .position(|range| range.end - range.start >= size)?;
+    let offset = free_ranges[index].start;
+    free_ranges[index].start += size;
+    if free_ranges[index].start == free_ranges[index].end {
+        free_ranges.remove(index);
+    }
+    Some(offset)
+}
+
+/// Puts `range` back into `free_ranges`, merging it with its free neighbours.
+fn give_range(free_ranges: &mut Vec<Range<BufferAddress>>, range: Range<BufferAddress>) {
+    let index = free_ranges
+        .iter()
+        .position(|free| free.start >= range.end)
+        .unwrap_or(free_ranges.len());
+    assert!(
+        index == 0 || free_ranges[index - 1].end <= range.start,
+        "Range {:?} is already free",
+        range
+    );
+
+    let merges_previous = index > 0 && free_ranges[index - 1].end == range.start;
+    let merges_next = index < free_ranges.len() && free_ranges[index].start == range.end;
+    match (merges_previous, merges_next) {
+        (true, true) => {
+            free_ranges[index - 1].end = free_ranges[index].end;
+            free_ranges.remove(index);
+        }
+        (true, false) => free_ranges[index - 1].end = range.end,
+        (false, true) => free_ranges[index].start = range.start,
+        (false, false) => free_ranges.insert(index, range),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_fit_reuse() {
+        let mut free_ranges = vec![0..1024];
+        assert_eq!(take_range(&mut free_ranges, 256), Some(0));
+        assert_eq!(take_range(&mut free_ranges, 512), Some(256));
+        assert_eq!(free_ranges, vec![768..1024]);
+        give_range(&mut free_ranges, 0..256);
+        assert_eq!(take_range(&mut free_ranges, 512), None);
+        assert_eq!(take_range(&mut free_ranges, 256), Some(0));
+        assert_eq!(take_range(&mut free_ranges, 256), Some(768));
+        assert!(free_ranges.is_empty());
+    }
+
+    #[test]
+    fn coalesce_previous() {
+        let mut free_ranges = vec![0..256, 768..1024];
+        give_range(&mut free_ranges, 256..512);
+        assert_eq!(free_ranges, vec![0..512, 768..1024]);
+    }
+
+    #[test]
+    fn coalesce_next() {
+        let mut free_ranges = vec![0..256, 768..1024];
+        give_range(&mut free_ranges, 512..768);
+        assert_eq!(free_ranges, vec![0..256, 512..1024]);
+    }
+
+    #[test]
+    fn coalesce_both() {
+        let mut free_ranges = vec![0..256, 512..1024];
+        give_range(&mut free_ranges, 256..512);
+        assert_eq!(free_ranges, vec![0..1024]);
+    }
+
+    #[test]
+    fn no_coalescing() {
+        let mut free_ranges = vec![0..256, 768..1024];
+        give_range(&mut free_ranges, 384..512);
+        assert_eq!(free_ranges, vec![0..256, 384..512, 768..1024]);
+    }
+
+    #[test]
+    #[should_panic(expected = "is already free")]
+    fn double_free() {
+        let mut free_ranges = vec![0..1024];
+        give_range(&mut free_ranges, 256..512);
+    }
+}
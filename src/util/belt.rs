@@ -1,6 +1,8 @@
+use super::align_to;
 use crate::{
-    Buffer, BufferAddress, BufferDescriptor, BufferSize, BufferUsage, BufferViewMut, CommandBuffer,
-    CommandEncoder, CommandEncoderDescriptor, Device, MapMode,
+    Buffer, BufferAddress, BufferCopyView, BufferDescriptor, BufferSize, BufferUsage,
+    BufferViewMut, CommandBuffer, CommandEncoder, CommandEncoderDescriptor, Device, Extent3d,
+    MapMode, TextureCopyView, TextureDataLayout,
 };
 use futures::{future::join_all, FutureExt};
 use std::{future::Future, mem, sync::mpsc};
@@ -9,6 +11,52 @@ struct Chunk {
     buffer: Buffer,
     size: BufferAddress,
     offset: BufferAddress,
+    /// Number of `recall` calls this chunk has spent in `free_chunks`.
+    idle_recalls: u32,
+}
+
+/// Chunk being mapped again by `recall`.
+///
+/// The chunk is sent back to the belt once it is mapped. If the future of `recall` is dropped
+/// before that, the map is cancelled and the chunk is sent back unmapped, for the next `recall`
+/// to map it again.
+struct RecallGuard {
+    chunk: Option<Chunk>,
+    sender: mpsc::Sender<Result<Chunk, Chunk>>,
+}
+
+impl RecallGuard {
+    fn finish(mut self) {
+        let chunk = self.chunk.take().unwrap();
+        // The belt may be gone already, in which case the chunk is simply dropped.
+        let _ = self.sender.send(Ok(chunk));
+    }
+}
+
+impl Drop for RecallGuard {
+    fn drop(&mut self) {
+        if let Some(chunk) = self.chunk.take() {
+            chunk.buffer.unmap();
+            let _ = self.sender.send(Err(chunk));
+        }
+    }
+}
+
+/// Memory usage of a [`StagingBelt`], as returned by [`StagingBelt::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StagingBeltStats {
+    /// Number of chunks written to since the last `flush`.
+    pub active_chunks: usize,
+    /// Total size of the active chunks.
+    pub active_bytes: BufferAddress,
+    /// Number of chunks flushed and not yet back from the GPU.
+    pub closed_chunks: usize,
+    /// Total size of the closed chunks.
+    pub closed_bytes: BufferAddress,
+    /// Number of chunks ready to be reused.
+    pub free_chunks: usize,
+    /// Total size of the free chunks.
+    pub free_bytes: BufferAddress,
 }
 
 /// Staging belt is a machine that uploads data.
@@ -16,6 +64,11 @@ struct Chunk {
 /// Internally it uses a ring-buffer of staging buffers that are sub-allocated.
 /// It has an advantage over `Queue.write_buffer` in a way that it returns a mutable slice,
 /// which you can fill to avoid an extra data copy.
+///
+/// Copies are recorded either into the belt's own encoder or into encoders passed by the
+/// caller. The chunks aren't tracked per encoder: `finish` and `flush` close all of them at
+/// once, so every encoder the belt recorded into since the previous `recall`, including the
+/// command buffer returned by `flush`, has to be submitted before the next `recall`.
 pub struct StagingBelt {
     chunk_size: BufferAddress,
     encoder: CommandEncoder,
@@ -25,8 +78,14 @@ pub struct StagingBelt {
     closed_chunks: Vec<Chunk>,
     /// Chunks that are back from the GPU and ready to be used.
     free_chunks: Vec<Chunk>,
-    sender: mpsc::Sender<Chunk>,
-    receiver: mpsc::Receiver<Chunk>,
+    /// Number and total size of the chunks being mapped by `recall`.
+    recalling: (usize, BufferAddress),
+    /// Upper bound on the total size of `free_chunks`.
+    free_memory_budget: Option<BufferAddress>,
+    /// Number of `recall` calls after which an unused free chunk is released.
+    max_idle_recalls: Option<u32>,
+    sender: mpsc::Sender<Result<Chunk, Chunk>>,
+    receiver: mpsc::Receiver<Result<Chunk, Chunk>>,
 }
 
 impl StagingBelt {
@@ -43,11 +102,45 @@ impl StagingBelt {
             active_chunks: Vec::new(),
             closed_chunks: Vec::new(),
             free_chunks: Vec::new(),
+            recalling: (0, 0),
+            free_memory_budget: None,
+            max_idle_recalls: None,
             sender,
             receiver,
         }
     }
 
+    /// Limit the memory retained by chunks that are ready to be reused to `budget` bytes.
+    ///
+    /// Chunks over the budget are released in `recall`, least recently used first.
+    /// Chunks with pending transfers don't count towards the budget. `None` removes the limit.
+    pub fn set_free_memory_budget(&mut self, budget: Option<BufferAddress>) {
+        self.free_memory_budget = budget;
+    }
+
+    /// Release chunks that stay unused for more than `recalls` calls to `recall`.
+    ///
+    /// This returns the memory of a load spike once uploads go back to normal.
+    /// `None` keeps unused chunks forever, which is the default.
+    pub fn set_max_idle_recalls(&mut self, recalls: Option<u32>) {
+        self.max_idle_recalls = recalls;
+    }
+
+    /// Report the number and size of the chunks in each state.
+    pub fn stats(&self) -> StagingBeltStats {
+        fn total(chunks: &[Chunk]) -> BufferAddress {
+            chunks.iter().map(|chunk| chunk.size).sum()
+        }
+        StagingBeltStats {
+            active_chunks: self.active_chunks.len(),
+            active_bytes: total(&self.active_chunks),
+            closed_chunks: self.closed_chunks.len() + self.recalling.0,
+            closed_bytes: total(&self.closed_chunks) + self.recalling.1,
+            free_chunks: self.free_chunks.len(),
+            free_bytes: total(&self.free_chunks),
+        }
+    }
+
     /// Allocate the staging belt slice of `size` to be uploaded into the `target` buffer
     /// at the specified offset.
     ///
@@ -59,20 +152,140 @@ impl StagingBelt {
         size: BufferSize,
         device: &Device,
     ) -> BufferViewMut {
+        self.write_buffer_impl(None, target, offset, size, device)
+    }
+
+    /// Like [`StagingBelt::write_buffer`], but records the copy into `encoder` instead of
+    /// the belt's own encoder.
+    ///
+    /// This lets uploads be interleaved with other work in the same command buffer.
+    /// [`StagingBelt::finish`] has to be called before `encoder` is submitted, and `encoder`
+    /// has to be submitted before the next [`StagingBelt::recall`].
+    pub fn write_buffer_with_encoder(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        target: &Buffer,
+        offset: BufferAddress,
+        size: BufferSize,
+        device: &Device,
+    ) -> BufferViewMut {
+        self.write_buffer_impl(Some(encoder), target, offset, size, device)
+    }
+
+    fn write_buffer_impl(
+        &mut self,
+        encoder: Option<&mut CommandEncoder>,
+        target: &Buffer,
+        offset: BufferAddress,
+        size: BufferSize,
+        device: &Device,
+    ) -> BufferViewMut {
+        let chunk_offset = self.allocate(size.get(), crate::COPY_BUFFER_ALIGNMENT, device);
+        let chunk = self.active_chunks.last().unwrap();
+        let encoder = encoder.unwrap_or(&mut self.encoder);
+        encoder.copy_buffer_to_buffer(&chunk.buffer, chunk_offset, target, offset, size.get());
+        chunk
+            .buffer
+            .slice(chunk_offset..chunk_offset + size.get())
+            .get_mapped_range_mut()
+    }
+
+    /// Allocate the staging belt rows to be uploaded into the `target` texture region of `size`.
+    ///
+    /// `bytes_per_row` is the size of one row of texels, or of one row of blocks
+    /// for compressed formats. The rows are padded to [`COPY_BYTES_PER_ROW_ALIGNMENT`](crate::COPY_BYTES_PER_ROW_ALIGNMENT)
+    /// in the staging buffer; the returned view hides the padding.
+    ///
+    /// The upload will only really be scheduled at the next `StagingBelt::flush` call.
+    ///
+    /// # Panics
+    ///
+    /// - `bytes_per_row` is zero.
+    pub fn write_texture(
+        &mut self,
+        target: TextureCopyView,
+        bytes_per_row: u32,
+        size: Extent3d,
+        device: &Device,
+    ) -> TextureRowsMut {
+        self.write_texture_impl(None, target, bytes_per_row, size, device)
+    }
+
+    /// Like [`StagingBelt::write_texture`], but records the copy into `encoder` instead of
+    /// the belt's own encoder.
+    ///
+    /// [`StagingBelt::finish`] has to be called before `encoder` is submitted, and `encoder`
+    /// has to be submitted before the next [`StagingBelt::recall`].
+    pub fn write_texture_with_encoder(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        target: TextureCopyView,
+        bytes_per_row: u32,
+        size: Extent3d,
+        device: &Device,
+    ) -> TextureRowsMut {
+        self.write_texture_impl(Some(encoder), target, bytes_per_row, size, device)
+    }
+
+    fn write_texture_impl(
+        &mut self,
+        encoder: Option<&mut CommandEncoder>,
+        target: TextureCopyView,
+        bytes_per_row: u32,
+        size: Extent3d,
+        device: &Device,
+    ) -> TextureRowsMut {
+        assert_ne!(bytes_per_row, 0, "Texture rows to write must not be empty");
+        let alignment = crate::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (bytes_per_row + alignment - 1) / alignment * alignment;
+        let rows = size.height * size.depth;
+        let total_size = padded_bytes_per_row as BufferAddress * rows as BufferAddress;
+
+        let chunk_offset = self.allocate(total_size, alignment as BufferAddress, device);
+        let chunk = self.active_chunks.last().unwrap();
+        let encoder = encoder.unwrap_or(&mut self.encoder);
+        encoder.copy_buffer_to_texture(
+            BufferCopyView {
+                buffer: &chunk.buffer,
+                layout: TextureDataLayout {
+                    offset: chunk_offset,
+                    bytes_per_row: padded_bytes_per_row,
+                    rows_per_image: size.height,
+                },
+            },
+            target,
+            size,
+        );
+        TextureRowsMut {
+            view: chunk
+                .buffer
+                .slice(chunk_offset..chunk_offset + total_size)
+                .get_mapped_range_mut(),
+            row_count: rows as usize,
+            bytes_per_row: bytes_per_row as usize,
+            padded_bytes_per_row: padded_bytes_per_row as usize,
+        }
+    }
+
+    /// Make room for `size` bytes at an offset aligned to `alignment` in an active chunk.
+    ///
+    /// The chunk is moved to the end of `active_chunks`, and the offset in it is returned.
+    fn allocate(
+        &mut self,
+        size: BufferAddress,
+        alignment: BufferAddress,
+        device: &Device,
+    ) -> BufferAddress {
         let mut chunk = if let Some(index) = self
             .active_chunks
             .iter()
-            .position(|chunk| chunk.offset + size.get() <= chunk.size)
+            .position(|chunk| align_to(chunk.offset, alignment) + size <= chunk.size)
         {
             self.active_chunks.swap_remove(index)
-        } else if let Some(index) = self
-            .free_chunks
-            .iter()
-            .position(|chunk| size.get() <= chunk.size)
-        {
+        } else if let Some(index) = self.free_chunks.iter().position(|chunk| size <= chunk.size) {
             self.free_chunks.swap_remove(index)
         } else {
-            let size = self.chunk_size.max(size.get());
+            let size = self.chunk_size.max(size);
             #[cfg(not(target_arch = "wasm32"))]
             wgc::span!(_guard, INFO, "Creating chunk of size {}", size);
             Chunk {
@@ -84,25 +297,15 @@ impl StagingBelt {
                 }),
                 size,
                 offset: 0,
+                idle_recalls: 0,
             }
         };
+        chunk.idle_recalls = 0;
 
-        self.encoder
-            .copy_buffer_to_buffer(&chunk.buffer, chunk.offset, target, offset, size.get());
-        let old_offset = chunk.offset;
-        chunk.offset += size.get();
-        let remainder = chunk.offset % crate::COPY_BUFFER_ALIGNMENT;
-        if remainder != 0 {
-            chunk.offset += crate::COPY_BUFFER_ALIGNMENT - remainder;
-        }
-
+        let offset = align_to(chunk.offset, alignment);
+        chunk.offset = align_to(offset + size, crate::COPY_BUFFER_ALIGNMENT);
         self.active_chunks.push(chunk);
-        self.active_chunks
-            .last()
-            .unwrap()
-            .buffer
-            .slice(old_offset..old_offset + size.get())
-            .get_mapped_range_mut()
+        offset
     }
 
     /// Produce a command buffer with all the accumulated transfers.
@@ -110,6 +313,26 @@ impl StagingBelt {
     /// At this point, all the partially used staging buffers are closed until
     /// the GPU is done copying the data from them.
     pub fn flush(&mut self, device: &Device) -> CommandBuffer {
+        self.finish();
+
+        mem::replace(
+            &mut self.encoder,
+            device.create_command_encoder(&CommandEncoderDescriptor::default()),
+        )
+        .finish()
+    }
+
+    /// Close all the partially used staging buffers until the GPU is done copying
+    /// the data from them.
+    ///
+    /// This has to be called before submitting the encoders passed to
+    /// [`StagingBelt::write_buffer_with_encoder`] and [`StagingBelt::write_texture_with_encoder`].
+    /// [`StagingBelt::flush`] calls it already.
+    ///
+    /// This also closes the chunks written through the belt's own encoder. If the belt's
+    /// own encoder was used since the last `flush`, call `flush` instead and submit its
+    /// command buffer too, or those copies would read chunks that `recall` maps again.
+    pub fn finish(&mut self) {
         #[cfg(not(target_arch = "wasm32"))]
         wgc::span!(_guard, DEBUG, "Flushing chunks");
 
@@ -117,32 +340,335 @@ impl StagingBelt {
             chunk.buffer.unmap();
             self.closed_chunks.push(chunk);
         }
-
-        mem::replace(
-            &mut self.encoder,
-            device.create_command_encoder(&CommandEncoderDescriptor::default()),
-        )
-        .finish()
     }
 
     /// Recall all of the closed buffers back for re-usal.
     ///
-    /// This has to be called after the command buffer produced by `flush` is submitted!
+    /// This has to be called after the command buffer produced by `flush` and all the
+    /// encoders the belt recorded into are submitted!
+    ///
+    /// Free chunks over the memory budget or idle for too long are released here.
+    /// Chunks of a previous `recall` whose future was dropped are mapped again.
     pub fn recall(&mut self) -> impl Future<Output = ()> + Send {
-        while let Ok(mut chunk) = self.receiver.try_recv() {
-            chunk.offset = 0;
-            self.free_chunks.push(chunk);
+        for chunk in self.free_chunks.iter_mut() {
+            chunk.idle_recalls += 1;
+        }
+        while let Ok(recalled) = self.receiver.try_recv() {
+            self.recalling.0 -= 1;
+            match recalled {
+                Ok(mut chunk) => {
+                    self.recalling.1 -= chunk.size;
+                    chunk.offset = 0;
+                    self.free_chunks.push(chunk);
+                }
+                Err(chunk) => {
+                    self.recalling.1 -= chunk.size;
+                    self.closed_chunks.push(chunk);
+                }
+            }
         }
+        self.evict_free_chunks();
 
+        for chunk in self.closed_chunks.iter() {
+            self.recalling.0 += 1;
+            self.recalling.1 += chunk.size;
+        }
         let sender_template = &self.sender;
         join_all(self.closed_chunks.drain(..).map(|chunk| {
-            let sender = sender_template.clone();
-            chunk
-                .buffer
-                .slice(..)
-                .map_async(MapMode::Write)
-                .inspect(move |_| sender.send(chunk).unwrap())
+            let map_future = chunk.buffer.slice(..).map_async(MapMode::Write);
+            let guard = RecallGuard {
+                chunk: Some(chunk),
+                sender: sender_template.clone(),
+            };
+            map_future.map(move |_| guard.finish())
         }))
         .map(|_| ())
     }
+
+    fn evict_free_chunks(&mut self) {
+        if let Some(max_idle_recalls) = self.max_idle_recalls {
+            self.free_chunks
+                .retain(|chunk| chunk.idle_recalls <= max_idle_recalls);
+        }
+        if let Some(budget) = self.free_memory_budget {
+            // Keep the most recently used chunks at the front.
+            self.free_chunks.sort_by_key(|chunk| chunk.idle_recalls);
+            let mut free_bytes: BufferAddress =
+                self.free_chunks.iter().map(|chunk| chunk.size).sum();
+            while free_bytes > budget {
+                let chunk = self.free_chunks.pop().unwrap();
+                #[cfg(not(target_arch = "wasm32"))]
+                wgc::span!(_guard, INFO, "Releasing chunk of size {}", chunk.size);
+                free_bytes -= chunk.size;
+            }
+        }
+    }
+}
+
+/// Write-only view of the rows of a texture upload allocated by [`StagingBelt::write_texture`].
+pub struct TextureRowsMut<'a> {
+    view: BufferViewMut<'a>,
+    row_count: usize,
+    bytes_per_row: usize,
+    padded_bytes_per_row: usize,
+}
+
+impl TextureRowsMut<'_> {
+    /// Number of rows, counting the rows of all array layers.
+    pub fn row_count(&self) -> usize {
+        self.row_count
+    }
+
+    /// Distance between the starts of two rows in the staging buffer.
+    pub fn padded_bytes_per_row(&self) -> usize {
+        self.padded_bytes_per_row
+    }
+
+    /// Returns the `index`-th row, without its padding.
+    ///
+    /// Rows of array layer `n` start at index `n * size.height`.
+    pub fn row_mut(&mut self, index: usize) -> &mut [u8] {
+        let start = index * self.padded_bytes_per_row;
+        &mut self.view[start..start + self.bytes_per_row]
+    }
+
+    /// Iterates over all rows, without their padding.
+    pub fn rows_mut(&mut self) -> impl Iterator<Item = &mut [u8]> {
+        let bytes_per_row = self.bytes_per_row;
+        self.view
+            .chunks_mut(self.padded_bytes_per_row)
+            .map(move |row| &mut row[..bytes_per_row])
+    }
+
+    /// Copies tightly packed rows from `data` into the staging buffer.
+    ///
+    /// # Panics
+    ///
+    /// - `data` is not exactly `row_count() * bytes_per_row` long.
+    pub fn copy_from_slice(&mut self, data: &[u8]) {
+        assert_eq!(
+            data.len(),
+            self.row_count() * self.bytes_per_row,
+            "Data size doesn't match the texture upload size"
+        );
+        for (row, source) in self.rows_mut().zip(data.chunks(self.bytes_per_row)) {
+            row.copy_from_slice(source);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::request_device, Origin3d, Queue, Texture, TextureDescriptor, TextureDimension,
+        TextureFormat, TextureUsage,
+    };
+
+    fn create_texture(device: &Device, size: Extent3d) -> Texture {
+        device.create_texture(&TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::R8Uint,
+            usage: TextureUsage::COPY_SRC | TextureUsage::COPY_DST,
+        })
+    }
+
+    #[test]
+    fn write_texture() {
+        let (device, queue) = request_device();
+        let size = Extent3d {
+            width: 3,
+            height: 2,
+            depth: 1,
+        };
+        let texture = create_texture(&device, size);
+        let mut belt = StagingBelt::new(1024, &device);
+        let target = TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+        };
+        {
+            let mut rows = belt.write_texture(target, 3, size, &device);
+            assert_eq!(rows.row_count(), 2);
+            assert_eq!(
+                rows.padded_bytes_per_row(),
+                crate::COPY_BYTES_PER_ROW_ALIGNMENT as usize
+            );
+            rows.copy_from_slice(&[1, 2, 3, 4, 5, 6]);
+        }
+        queue.submit(Some(belt.flush(&device)));
+
+        let data =
+            futures::executor::block_on(crate::util::read_texture(&device, &queue, &texture, 0, 0))
+                .unwrap();
+        assert_eq!(data, vec![1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn write_buffer_with_encoder() {
+        let (device, queue) = request_device();
+        let target = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: 8,
+            usage: BufferUsage::COPY_DST | BufferUsage::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut belt = StagingBelt::new(1024, &device);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        belt.write_buffer_with_encoder(
+            &mut encoder,
+            &target,
+            0,
+            BufferSize::new(8).unwrap(),
+            &device,
+        )
+        .copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+        belt.finish();
+        // Only the caller's encoder carries the copy.
+        queue.submit(Some(encoder.finish()));
+        futures::executor::block_on(belt.recall());
+
+        let slice = target.slice(..);
+        futures::executor::block_on(slice.map_async(MapMode::Read)).unwrap();
+        assert_eq!(&*slice.get_mapped_range(), &[1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Texture rows to write must not be empty")]
+    fn write_texture_rejects_empty_rows() {
+        let (device, _queue) = request_device();
+        let size = Extent3d {
+            width: 1,
+            height: 1,
+            depth: 1,
+        };
+        let texture = create_texture(&device, size);
+        let mut belt = StagingBelt::new(1024, &device);
+        let target = TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+        };
+        belt.write_texture(target, 0, size, &device);
+    }
+
+    fn write_chunks(belt: &mut StagingBelt, count: usize, device: &Device, queue: &Queue) {
+        let target = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: 1024,
+            usage: BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+        for _ in 0..count {
+            let size = BufferSize::new(1024).unwrap();
+            belt.write_buffer(&target, 0, size, device)
+                .copy_from_slice(&[1; 1024]);
+        }
+        queue.submit(Some(belt.flush(device)));
+    }
+
+    #[test]
+    fn stats() {
+        let (device, queue) = request_device();
+        let mut belt = StagingBelt::new(1024, &device);
+        let target = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+        drop(belt.write_buffer(&target, 0, BufferSize::new(16).unwrap(), &device));
+        assert_eq!(
+            belt.stats(),
+            StagingBeltStats {
+                active_chunks: 1,
+                active_bytes: 1024,
+                ..StagingBeltStats::default()
+            }
+        );
+
+        queue.submit(Some(belt.flush(&device)));
+        let closed = StagingBeltStats {
+            closed_chunks: 1,
+            closed_bytes: 1024,
+            ..StagingBeltStats::default()
+        };
+        assert_eq!(belt.stats(), closed);
+        futures::executor::block_on(belt.recall());
+        assert_eq!(belt.stats(), closed);
+        futures::executor::block_on(belt.recall());
+        assert_eq!(
+            belt.stats(),
+            StagingBeltStats {
+                free_chunks: 1,
+                free_bytes: 1024,
+                ..StagingBeltStats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn dropped_recall() {
+        let (device, queue) = request_device();
+        let mut belt = StagingBelt::new(1024, &device);
+        write_chunks(&mut belt, 2, &device, &queue);
+        drop(belt.recall());
+        // The chunks of the dropped recall are mapped again instead of being released,
+        // and come back on the recall after that.
+        futures::executor::block_on(belt.recall());
+        futures::executor::block_on(belt.recall());
+        assert_eq!(
+            belt.stats(),
+            StagingBeltStats {
+                free_chunks: 2,
+                free_bytes: 2048,
+                ..StagingBeltStats::default()
+            }
+        );
+        // And reused by the next writes.
+        write_chunks(&mut belt, 2, &device, &queue);
+        assert_eq!(
+            belt.stats(),
+            StagingBeltStats {
+                closed_chunks: 2,
+                closed_bytes: 2048,
+                ..StagingBeltStats::default()
+            }
+        );
+    }
+
+    #[test]
+    fn free_memory_budget() {
+        let (device, queue) = request_device();
+        let mut belt = StagingBelt::new(1024, &device);
+        write_chunks(&mut belt, 3, &device, &queue);
+        futures::executor::block_on(belt.recall());
+        futures::executor::block_on(belt.recall());
+        assert_eq!(belt.stats().free_bytes, 3 * 1024);
+
+        belt.set_free_memory_budget(Some(2 * 1024));
+        futures::executor::block_on(belt.recall());
+        assert_eq!(belt.stats().free_chunks, 2);
+        assert_eq!(belt.stats().free_bytes, 2 * 1024);
+    }
+
+    #[test]
+    fn max_idle_recalls() {
+        let (device, queue) = request_device();
+        let mut belt = StagingBelt::new(1024, &device);
+        write_chunks(&mut belt, 1, &device, &queue);
+        futures::executor::block_on(belt.recall());
+        futures::executor::block_on(belt.recall());
+        belt.set_max_idle_recalls(Some(1));
+
+        futures::executor::block_on(belt.recall());
+        assert_eq!(belt.stats().free_chunks, 1);
+        futures::executor::block_on(belt.recall());
+        assert_eq!(belt.stats().free_chunks, 0);
+    }
 }
@@ -0,0 +1,455 @@
+use super::{texture_format_info, UniformRing};
+use crate::{
+    AddressMode, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, BlendDescriptor, BufferAddress, BufferSize,
+    ColorStateDescriptor, ColorWrite, CommandEncoder, CullMode, Device, FilterMode, FrontFace,
+    IndexFormat, LoadOp, Operations, PipelineLayout, PipelineLayoutDescriptor, PrimitiveTopology,
+    ProgrammableStageDescriptor, Queue, RasterizationStateDescriptor,
+    RenderPassColorAttachmentDescriptor, RenderPassDescriptor, RenderPipeline,
+    RenderPipelineDescriptor, Sampler, SamplerDescriptor, ShaderModule, ShaderStage,
+    TextureComponentType, TextureFormat, TextureView, TextureViewDimension, VertexStateDescriptor,
+};
+use std::{collections::HashMap, future::Future};
+
+/// Size of the source region uniform, an offset and a size in texture coordinates.
+const REGION_SIZE: BufferAddress = 16;
+
+/// Rectangle of a texture view, in texels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BlitRect {
+    /// Left edge of the rectangle.
+    pub x: u32,
+    /// Top edge of the rectangle.
+    pub y: u32,
+    /// Width of the rectangle.
+    pub width: u32,
+    /// Height of the rectangle.
+    pub height: u32,
+}
+
+/// Source of a [`Blitter::blit`].
+#[derive(Clone, Copy)]
+pub struct BlitSource<'a> {
+    /// 2D view of a single mip level and array layer, with a float sample type.
+    pub view: &'a TextureView,
+    /// Width of the view, in texels.
+    pub width: u32,
+    /// Height of the view, in texels.
+    pub height: u32,
+    /// The region of the view to copy from.
+    pub rect: BlitRect,
+}
+
+/// Destination of a [`Blitter::blit`].
+#[derive(Clone, Copy)]
+pub struct BlitDestination<'a> {
+    /// 2D view of a single mip level and array layer, usable as an output attachment.
+    pub view: &'a TextureView,
+    /// Format of the view.
+    pub format: TextureFormat,
+    /// The region of the view to copy to. The rest of the view is left untouched.
+    pub rect: BlitRect,
+}
+
+/// Blitter is a machine that copies regions between texture views of any size and format.
+///
+/// Each copy is a render pass drawing a triangle over the destination region that samples
+/// the source region, so the data is scaled with the chosen filter, and converted from the
+/// source format to the destination one, e.g. from `Rgba16Float` to `Bgra8UnormSrgb`.
+/// Use [`CommandEncoder::copy_texture_to_texture`] instead when the formats and sizes match.
+///
+/// The render pipeline of each destination format is created on first use and kept
+/// for the next ones. The source regions are written into a [`UniformRing`], so a frame
+/// goes through `blit`, `finish`, submission and `recall`, like with the ring itself.
+pub struct Blitter {
+    bind_group_layout: BindGroupLayout,
+    pipeline_layout: PipelineLayout,
+    vertex_module: ShaderModule,
+    fragment_module: ShaderModule,
+    linear_sampler: Sampler,
+    nearest_sampler: Sampler,
+    pipelines: HashMap<TextureFormat, RenderPipeline>,
+    regions: UniformRing,
+}
+
+impl Blitter {
+    /// Create a new blitter.
+    pub fn new(device: &Device) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[
+                BindGroupLayoutEntry::new(
+                    0,
+                    ShaderStage::FRAGMENT,
+                    BindingType::SampledTexture {
+                        multisampled: false,
+                        component_type: TextureComponentType::Float,
+                        dimension: TextureViewDimension::D2,
+                    },
+                ),
+                BindGroupLayoutEntry::new(
+                    1,
+                    ShaderStage::FRAGMENT,
+                    BindingType::Sampler { comparison: false },
+                ),
+                BindGroupLayoutEntry::new(
+                    2,
+                    ShaderStage::FRAGMENT,
+                    BindingType::UniformBuffer {
+                        dynamic: true,
+                        min_binding_size: BufferSize::new(REGION_SIZE),
+                    },
+                ),
+            ],
+            label: Some("blit"),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        Blitter {
+            linear_sampler: create_sampler(device, FilterMode::Linear),
+            nearest_sampler: create_sampler(device, FilterMode::Nearest),
+            bind_group_layout,
+            pipeline_layout,
+            vertex_module: device.create_shader_module(crate::include_spirv!("blit.vert.spv")),
+            fragment_module: device
+                .create_shader_module(crate::include_spirv!("blit_region.frag.spv")),
+            pipelines: HashMap::new(),
+            regions: UniformRing::new(64 * crate::BIND_BUFFER_ALIGNMENT),
+        }
+    }
+
+    /// Record a copy of the `source` region into the `destination` region into `encoder`,
+    /// scaling it with `filter`.
+    ///
+    /// The source and destination can't be the same subresource of a texture, since it
+    /// can't be sampled and rendered to in the same pass. Passing the same view for both
+    /// panics, while distinct views of the same subresource are a usage conflict that is
+    /// left to the validation of the backend.
+    ///
+    /// [`Blitter::finish`] has to be called before `encoder` is submitted.
+    ///
+    /// # Panics
+    ///
+    /// - The destination format is not a renderable color format.
+    /// - `source` and `destination` have the same view.
+    pub fn blit(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        encoder: &mut CommandEncoder,
+        source: BlitSource,
+        destination: BlitDestination,
+        filter: FilterMode,
+    ) {
+        let format = destination.format;
+        let info = texture_format_info(format);
+        assert!(
+            info.renderable && !info.depth,
+            "Can't blit into format {:?}, which is not a renderable color format",
+            format
+        );
+        assert!(
+            !std::ptr::eq(source.view, destination.view),
+            "Can't blit a texture view into itself"
+        );
+
+        let pipeline_layout = &self.pipeline_layout;
+        let vertex_module = &self.vertex_module;
+        let fragment_module = &self.fragment_module;
+        let pipeline = &*self.pipelines.entry(format).or_insert_with(|| {
+            create_pipeline(
+                device,
+                pipeline_layout,
+                vertex_module,
+                fragment_module,
+                format,
+            )
+        });
+        let sampler = match filter {
+            FilterMode::Linear => &self.linear_sampler,
+            FilterMode::Nearest => &self.nearest_sampler,
+        };
+
+        let (width, height) = (source.width as f32, source.height as f32);
+        let rect = source.rect;
+        let region = [
+            rect.x as f32 / width,
+            rect.y as f32 / height,
+            rect.width as f32 / width,
+            rect.height as f32 / height,
+        ];
+        let allocation = self
+            .regions
+            .write(queue, bytemuck::cast_slice(&region), device);
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            layout: &self.bind_group_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(source.view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(sampler),
+                },
+                BindGroupEntry {
+                    binding: 2,
+                    resource: BindingResource::Buffer(
+                        self.regions
+                            .buffer(allocation.buffer_index)
+                            .slice(..REGION_SIZE),
+                    ),
+                },
+            ],
+            label: Some("blit"),
+        });
+
+        let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+            color_attachments: &[RenderPassColorAttachmentDescriptor {
+                attachment: destination.view,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            }],
+            depth_stencil_attachment: None,
+        });
+        let rect = destination.rect;
+        pass.set_pipeline(pipeline);
+        pass.set_bind_group(0, &bind_group, &[allocation.offset]);
+        pass.set_viewport(
+            rect.x as f32,
+            rect.y as f32,
+            rect.width as f32,
+            rect.height as f32,
+            0.0,
+            1.0,
+        );
+        pass.draw(0..3, 0..1);
+    }
+
+    /// Close the source regions written in this frame until the GPU is done with them.
+    ///
+    /// This has to be called before the frame is submitted to `queue`!
+    pub fn finish(&mut self, queue: &Queue) {
+        self.regions.finish(queue);
+    }
+
+    /// Recall the source regions of submitted frames for re-usal.
+    ///
+    /// This has to be called after the frame closed by `finish` is submitted!
+    pub fn recall(&mut self) -> impl Future<Output = ()> + Send {
+        self.regions.recall()
+    }
+}
+
+/// Creates a sampler for blits, clamping to the edge of the source.
+pub(super) fn create_sampler(device: &Device, filter: FilterMode) -> Sampler {
+    device.create_sampler(&SamplerDescriptor {
+        label: Some("blit"),
+        address_mode_u: AddressMode::ClampToEdge,
+        address_mode_v: AddressMode::ClampToEdge,
+        address_mode_w: AddressMode::ClampToEdge,
+        mag_filter: filter,
+        min_filter: filter,
+        mipmap_filter: FilterMode::Nearest,
+        ..Default::default()
+    })
+}
+
+/// Creates a pipeline drawing a triangle over the target with `blit.vert`, writing to `format`.
+pub(super) fn create_pipeline(
+    device: &Device,
+    layout: &PipelineLayout,
+    vertex_module: &ShaderModule,
+    fragment_module: &ShaderModule,
+    format: TextureFormat,
+) -> RenderPipeline {
+    #[cfg(not(target_arch = "wasm32"))]
+    wgc::span!(_guard, INFO, "Creating blit pipeline for {:?}", format);
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        layout,
+        vertex_stage: ProgrammableStageDescriptor {
+            module: vertex_module,
+            entry_point: "main",
+        },
+        fragment_stage: Some(ProgrammableStageDescriptor {
+            module: fragment_module,
+            entry_point: "main",
+        }),
+        rasterization_state: Some(RasterizationStateDescriptor {
+            front_face: FrontFace::Ccw,
+            cull_mode: CullMode::None,
+            depth_bias: 0,
+            depth_bias_slope_scale: 0.0,
+            depth_bias_clamp: 0.0,
+        }),
+        primitive_topology: PrimitiveTopology::TriangleList,
+        color_states: &[ColorStateDescriptor {
+            format,
+            color_blend: BlendDescriptor::REPLACE,
+            alpha_blend: BlendDescriptor::REPLACE,
+            write_mask: ColorWrite::ALL,
+        }],
+        depth_stencil_state: None,
+        vertex_state: VertexStateDescriptor {
+            index_format: IndexFormat::Uint16,
+            vertex_buffers: &[],
+        },
+        sample_count: 1,
+        sample_mask: !0,
+        alpha_to_coverage_enabled: false,
+    })
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use crate::mock::{request_device, Command};
+
+    #[test]
+    fn blit_regions_share_a_ring() {
+        use crate::{
+            util::{BlitDestination, BlitRect, BlitSource, Blitter},
+            CommandEncoderDescriptor, Extent3d, FilterMode, TextureDescriptor, TextureDimension,
+            TextureFormat, TextureUsage, BIND_BUFFER_ALIGNMENT,
+        };
+
+        let (device, queue) = request_device();
+        let create_texture = |width, height, format, usage| {
+            device.create_texture(&TextureDescriptor {
+                label: None,
+                size: Extent3d {
+                    width,
+                    height,
+                    depth: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format,
+                usage,
+            })
+        };
+        let source = create_texture(8, 8, TextureFormat::Rgba16Float, TextureUsage::SAMPLED);
+        let destination = create_texture(
+            4,
+            4,
+            TextureFormat::Bgra8UnormSrgb,
+            TextureUsage::OUTPUT_ATTACHMENT,
+        );
+        let source_view = source.create_default_view();
+        let destination_view = destination.create_default_view();
+
+        let mut blitter = Blitter::new(&device);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        for &x in &[1, 3] {
+            blitter.blit(
+                &device,
+                &queue,
+                &mut encoder,
+                BlitSource {
+                    view: &source_view,
+                    width: 8,
+                    height: 8,
+                    rect: BlitRect {
+                        x: 0,
+                        y: 0,
+                        width: 8,
+                        height: 8,
+                    },
+                },
+                BlitDestination {
+                    view: &destination_view,
+                    format: TextureFormat::Bgra8UnormSrgb,
+                    rect: BlitRect {
+                        x,
+                        y: 2,
+                        width: 2,
+                        height: 2,
+                    },
+                },
+                FilterMode::Linear,
+            );
+        }
+        blitter.finish(&queue);
+        queue.submit(Some(encoder.finish()));
+        futures::executor::block_on(blitter.recall());
+
+        // Both regions share a buffer, at different dynamic offsets.
+        let log = queue.command_log();
+        let offsets = log
+            .iter()
+            .filter_map(|command| match command {
+                Command::SetBindGroup { offsets, .. } => Some(offsets.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(offsets, vec![vec![0], vec![BIND_BUFFER_ALIGNMENT as u32]]);
+
+        assert!(log.contains(&Command::SetViewport {
+            x: 1.0,
+            y: 2.0,
+            width: 2.0,
+            height: 2.0,
+            min_depth: 0.0,
+            max_depth: 1.0,
+        }));
+        assert!(log.contains(&Command::Draw {
+            vertices: 0..3,
+            instances: 0..1,
+        }));
+
+        // The next frame reuses the recalled buffer.
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        blitter.blit(
+            &device,
+            &queue,
+            &mut encoder,
+            BlitSource {
+                view: &source_view,
+                width: 8,
+                height: 8,
+                rect: BlitRect {
+                    x: 0,
+                    y: 0,
+                    width: 8,
+                    height: 8,
+                },
+            },
+            BlitDestination {
+                view: &destination_view,
+                format: TextureFormat::Bgra8UnormSrgb,
+                rect: BlitRect {
+                    x: 1,
+                    y: 2,
+                    width: 2,
+                    height: 2,
+                },
+            },
+            FilterMode::Linear,
+        );
+        blitter.finish(&queue);
+        queue.submit(Some(encoder.finish()));
+
+        let region_writes = queue
+            .command_log()
+            .into_iter()
+            .filter_map(|command| match command {
+                Command::WriteBuffer {
+                    buffer,
+                    offset,
+                    size: 16,
+                } => Some((buffer, offset)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let buffer = region_writes[0].0;
+        assert_eq!(
+            region_writes,
+            vec![(buffer, 0), (buffer, BIND_BUFFER_ALIGNMENT), (buffer, 0)]
+        );
+    }
+}
@@ -0,0 +1,11 @@
+#version 450
+
+layout(location = 0) out vec2 v_TexCoord;
+
+void main() {
+    // A triangle covering the whole target, with texture coordinates
+    // going from (0, 0) at the top left to (1, 1) at the bottom right.
+    vec2 tc = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+    v_TexCoord = tc;
+    gl_Position = vec4(tc.x * 2.0 - 1.0, 1.0 - tc.y * 2.0, 0.5, 1.0);
+}
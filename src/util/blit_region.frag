@@ -0,0 +1,15 @@
+#version 450
+
+layout(location = 0) in vec2 v_TexCoord;
+layout(location = 0) out vec4 o_Target;
+layout(set = 0, binding = 0) uniform texture2D t_Color;
+layout(set = 0, binding = 1) uniform sampler s_Color;
+layout(set = 0, binding = 2) uniform Locals {
+    // Offset and size of the source region, in texture coordinates.
+    vec4 u_Region;
+};
+
+void main() {
+    vec2 tc = u_Region.xy + v_TexCoord * u_Region.zw;
+    o_Target = textureLod(sampler2D(t_Color, s_Color), tc, 0.0);
+}
@@ -0,0 +1,105 @@
+use crate::{TextureComponentType, TextureFormat};
+
+/// Properties of a [`TextureFormat`], as returned by [`texture_format_info`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureFormatInfo {
+    /// Width and height of a block of texels, `(1, 1)` for uncompressed formats.
+    pub block_dimensions: (u8, u8),
+    /// Size of a block of texels in bytes.
+    ///
+    /// Formats with an unspecified layout, like [`TextureFormat::Depth24Plus`],
+    /// report the size they usually have in memory, but aren't `copyable`.
+    pub block_size: u8,
+    /// Type of the values a shader samples from the format.
+    pub component_type: TextureComponentType,
+    /// The format is converted from sRGB when sampled, and to sRGB when rendered to.
+    pub srgb: bool,
+    /// The format has a depth aspect.
+    pub depth: bool,
+    /// The format has a stencil aspect.
+    pub stencil: bool,
+    /// The format can be sampled with a filtering sampler.
+    pub filterable: bool,
+    /// The format can be used as a color, depth or stencil output attachment.
+    pub renderable: bool,
+    /// Texels of the format can be copied between textures and buffers.
+    pub copyable: bool,
+}
+
+/// Returns the properties of `format`.
+pub fn texture_format_info(format: TextureFormat) -> TextureFormatInfo {
+    use TextureComponentType::{Float, Sint, Uint};
+    use TextureFormat as Tf;
+
+    let (block_size, component_type, filterable, renderable) = match format {
+        // Normal 8 bit formats
+        Tf::R8Unorm => (1, Float, true, true),
+        Tf::R8Snorm => (1, Float, true, false),
+        Tf::R8Uint => (1, Uint, false, true),
+        Tf::R8Sint => (1, Sint, false, true),
+        // Normal 16 bit formats
+        Tf::R16Uint => (2, Uint, false, true),
+        Tf::R16Sint => (2, Sint, false, true),
+        Tf::R16Float => (2, Float, true, true),
+        Tf::Rg8Unorm => (2, Float, true, true),
+        Tf::Rg8Snorm => (2, Float, true, false),
+        Tf::Rg8Uint => (2, Uint, false, true),
+        Tf::Rg8Sint => (2, Sint, false, true),
+        // Normal 32 bit formats
+        Tf::R32Uint => (4, Uint, false, true),
+        Tf::R32Sint => (4, Sint, false, true),
+        Tf::R32Float => (4, Float, false, true),
+        Tf::Rg16Uint => (4, Uint, false, true),
+        Tf::Rg16Sint => (4, Sint, false, true),
+        Tf::Rg16Float => (4, Float, true, true),
+        Tf::Rgba8Unorm => (4, Float, true, true),
+        Tf::Rgba8UnormSrgb => (4, Float, true, true),
+        Tf::Rgba8Snorm => (4, Float, true, false),
+        Tf::Rgba8Uint => (4, Uint, false, true),
+        Tf::Rgba8Sint => (4, Sint, false, true),
+        Tf::Bgra8Unorm => (4, Float, true, true),
+        Tf::Bgra8UnormSrgb => (4, Float, true, true),
+        // Packed 32 bit formats
+        Tf::Rgb10a2Unorm => (4, Float, true, true),
+        Tf::Rg11b10Float => (4, Float, true, false),
+        // Normal 64 bit formats
+        Tf::Rg32Uint => (8, Uint, false, true),
+        Tf::Rg32Sint => (8, Sint, false, true),
+        Tf::Rg32Float => (8, Float, false, true),
+        Tf::Rgba16Uint => (8, Uint, false, true),
+        Tf::Rgba16Sint => (8, Sint, false, true),
+        Tf::Rgba16Float => (8, Float, true, true),
+        // Normal 128 bit formats
+        Tf::Rgba32Uint => (16, Uint, false, true),
+        Tf::Rgba32Sint => (16, Sint, false, true),
+        Tf::Rgba32Float => (16, Float, false, true),
+        // Depth and stencil formats
+        Tf::Depth32Float => (4, Float, false, true),
+        Tf::Depth24Plus => (4, Float, false, true),
+        Tf::Depth24PlusStencil8 => (4, Float, false, true),
+    };
+
+    TextureFormatInfo {
+        block_dimensions: (1, 1),
+        block_size,
+        component_type,
+        srgb: match format {
+            Tf::Rgba8UnormSrgb | Tf::Bgra8UnormSrgb => true,
+            _ => false,
+        },
+        depth: match format {
+            Tf::Depth32Float | Tf::Depth24Plus | Tf::Depth24PlusStencil8 => true,
+            _ => false,
+        },
+        stencil: match format {
+            Tf::Depth24PlusStencil8 => true,
+            _ => false,
+        },
+        filterable,
+        renderable,
+        copyable: match format {
+            Tf::Depth24Plus | Tf::Depth24PlusStencil8 => false,
+            _ => true,
+        },
+    }
+}
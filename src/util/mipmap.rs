@@ -0,0 +1,214 @@
+use super::{
+    blit::{create_pipeline, create_sampler},
+    texture_format_info,
+};
+use crate::{
+    BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, Color, CommandEncoder, Device, FilterMode,
+    LoadOp, Operations, PipelineLayout, PipelineLayoutDescriptor,
+    RenderPassColorAttachmentDescriptor, RenderPassDescriptor, RenderPipeline, Sampler,
+    ShaderModule, ShaderStage, Texture, TextureAspect, TextureComponentType, TextureDimension,
+    TextureFormat, TextureViewDescriptor, TextureViewDimension,
+};
+use std::collections::HashMap;
+
+/// Mipmap generator is a machine that fills the mip levels of textures.
+///
+/// Each level is rendered from the previous one, so the textures need the `SAMPLED` and
+/// `OUTPUT_ATTACHMENT` usages, and a format that is filterable and renderable according
+/// to [`TextureFormatInfo`](super::TextureFormatInfo). Textures with an sRGB format are
+/// downsampled in linear space, since sampling decodes them and rendering encodes them back.
+///
+/// The render pipeline of each format is created on first use and kept for the next ones.
+pub struct MipmapGenerator {
+    bind_group_layout: BindGroupLayout,
+    pipeline_layout: PipelineLayout,
+    vertex_module: ShaderModule,
+    fragment_module: ShaderModule,
+    linear_sampler: Sampler,
+    nearest_sampler: Sampler,
+    pipelines: HashMap<TextureFormat, RenderPipeline>,
+}
+
+impl MipmapGenerator {
+    /// Create a new mipmap generator.
+    pub fn new(device: &Device) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            entries: &[
+                BindGroupLayoutEntry::new(
+                    0,
+                    ShaderStage::FRAGMENT,
+                    BindingType::SampledTexture {
+                        multisampled: false,
+                        component_type: TextureComponentType::Float,
+                        dimension: TextureViewDimension::D2,
+                    },
+                ),
+                BindGroupLayoutEntry::new(
+                    1,
+                    ShaderStage::FRAGMENT,
+                    BindingType::Sampler { comparison: false },
+                ),
+            ],
+            label: Some("mipmap"),
+        });
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        MipmapGenerator {
+            linear_sampler: create_sampler(device, FilterMode::Linear),
+            nearest_sampler: create_sampler(device, FilterMode::Nearest),
+            bind_group_layout,
+            pipeline_layout,
+            vertex_module: device.create_shader_module(crate::include_spirv!("blit.vert.spv")),
+            fragment_module: device.create_shader_module(crate::include_spirv!("blit.frag.spv")),
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// Record the rendering of all the mip levels of `texture` past the first one into `encoder`.
+    ///
+    /// With [`FilterMode::Linear`], each texel is a bilinear sample of the previous level at
+    /// its centre. Where the previous level has an even size, that is the average of the 2x2
+    /// texels it covers. Where the size is odd, the texels covered are weighted unevenly and
+    /// some don't contribute at all, as there is no box filter averaging all of them. With
+    /// [`FilterMode::Nearest`], each texel is one of the texels it covers. Every array layer
+    /// is filled, which covers 2D arrays and cube maps.
+    ///
+    /// # Panics
+    ///
+    /// - `texture` is not a 2D texture.
+    /// - The format of `texture` is not a filterable and renderable color format.
+    pub fn generate(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        texture: &Texture,
+        filter: FilterMode,
+    ) {
+        assert_eq!(
+            texture.dimension,
+            TextureDimension::D2,
+            "Mipmaps can only be generated for 2D textures"
+        );
+        let format = texture.format;
+        let info = texture_format_info(format);
+        assert!(
+            info.filterable && info.renderable && !info.depth,
+            "Mipmaps can't be generated for format {:?}, which is not filterable and renderable",
+            format
+        );
+
+        let bind_group_layout = &self.bind_group_layout;
+        let pipeline_layout = &self.pipeline_layout;
+        let vertex_module = &self.vertex_module;
+        let fragment_module = &self.fragment_module;
+        let pipeline = &*self.pipelines.entry(format).or_insert_with(|| {
+            create_pipeline(
+                device,
+                pipeline_layout,
+                vertex_module,
+                fragment_module,
+                format,
+            )
+        });
+        let sampler = match filter {
+            FilterMode::Linear => &self.linear_sampler,
+            FilterMode::Nearest => &self.nearest_sampler,
+        };
+
+        for layer in 0..texture.size.depth {
+            let views = (0..texture.mip_level_count)
+                .map(|mip_level| {
+                    texture.create_view(&TextureViewDescriptor {
+                        label: Some("mipmap"),
+                        format,
+                        dimension: TextureViewDimension::D2,
+                        aspect: TextureAspect::All,
+                        base_mip_level: mip_level,
+                        level_count: 1,
+                        base_array_layer: layer,
+                        array_layer_count: 1,
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            for target in 1..views.len() {
+                let bind_group = device.create_bind_group(&BindGroupDescriptor {
+                    layout: bind_group_layout,
+                    entries: &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::TextureView(&views[target - 1]),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::Sampler(sampler),
+                        },
+                    ],
+                    label: Some("mipmap"),
+                });
+                let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                    color_attachments: &[RenderPassColorAttachmentDescriptor {
+                        attachment: &views[target],
+                        resolve_target: None,
+                        ops: Operations {
+                            load: LoadOp::Clear(Color::TRANSPARENT),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                });
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use crate::mock::{request_device, Command};
+
+    #[test]
+    fn generate_fills_every_layer() {
+        use crate::{
+            util::MipmapGenerator, CommandEncoderDescriptor, Extent3d, FilterMode,
+            TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+        };
+
+        let (device, queue) = request_device();
+        let texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: 4,
+                height: 4,
+                depth: 6,
+            },
+            mip_level_count: 3,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8UnormSrgb,
+            usage: TextureUsage::SAMPLED | TextureUsage::OUTPUT_ATTACHMENT,
+        });
+        let mut generator = MipmapGenerator::new(&device);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        generator.generate(&device, &mut encoder, &texture, FilterMode::Linear);
+        generator.generate(&device, &mut encoder, &texture, FilterMode::Nearest);
+        queue.submit(Some(encoder.finish()));
+
+        // Two levels to fill in each of the six layers, twice.
+        let draw = Command::Draw {
+            vertices: 0..3,
+            instances: 0..1,
+        };
+        let draws = queue
+            .command_log()
+            .into_iter()
+            .filter(|command| *command == draw)
+            .count();
+        assert_eq!(draws, 24);
+    }
+}
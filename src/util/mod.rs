@@ -1,11 +1,31 @@
 //! Utility structures and functions.
 
+mod allocator;
 mod belt;
+mod blit;
+mod format;
+mod mipmap;
+mod readback;
+mod texture;
+mod typed;
+mod uniform;
+mod vec;
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "subscriber"))]
 pub use wgc::logging::subscriber::{initialize_default_subscriber, ChromeTracingLayer};
 
-pub use belt::StagingBelt;
+pub use allocator::{BufferAllocation, BufferAllocator};
+pub use belt::{StagingBelt, StagingBeltStats, TextureRowsMut};
+pub use blit::{BlitDestination, BlitRect, BlitSource, Blitter};
+pub use format::{texture_format_info, TextureFormatInfo};
+pub use mipmap::MipmapGenerator;
+pub use readback::{Readback, ReadbackBelt, ReadbackView};
+pub use texture::{read_texture, TextureUploader};
+pub use typed::{TypedBuffer, TypedBufferSlice, TypedBufferView, TypedBufferViewMut};
+pub use uniform::{UniformAllocation, UniformRing};
+pub use vec::GpuVec;
+
+pub(crate) use texture::mip_extent;
 
 /// Wrapper aligning contents to at least 4.
 #[repr(align(4))]
@@ -34,3 +54,11 @@ pub fn make_spirv<'a>(data: &'a [u8]) -> super::ShaderModuleSource<'a> {
     );
     super::ShaderModuleSource::SpirV(words)
 }
+
+/// Rounds `offset` up to a multiple of `alignment`.
+fn align_to(offset: crate::BufferAddress, alignment: crate::BufferAddress) -> crate::BufferAddress {
+    match offset % alignment {
+        0 => offset,
+        remainder => offset + alignment - remainder,
+    }
+}
@@ -0,0 +1,377 @@
+use crate::{
+    Buffer, BufferAddress, BufferAsyncError, BufferCopyView, BufferDescriptor, BufferSize,
+    BufferUsage, CommandEncoder, Context, Device, Extent3d, MapMode, TextureCopyView,
+    TextureDataLayout,
+};
+use futures::FutureExt;
+use std::{future::Future, ops::Deref, sync::mpsc};
+
+struct Chunk {
+    buffer: Buffer,
+    size: BufferAddress,
+}
+
+/// Readback belt is a machine that downloads data.
+///
+/// It is the download counterpart of [`StagingBelt`](super::StagingBelt):
+/// it keeps a pool of readback buffers, records copies into them and maps
+/// them for reading once the copies have been submitted. A buffer goes back
+/// to the pool when the [`ReadbackView`] of its data is dropped.
+pub struct ReadbackBelt {
+    chunk_size: BufferAddress,
+    /// Chunks that are back from the user and ready to be used.
+    free_chunks: Vec<Chunk>,
+    sender: mpsc::Sender<Chunk>,
+    receiver: mpsc::Receiver<Chunk>,
+}
+
+impl ReadbackBelt {
+    /// Create a new readback belt.
+    ///
+    /// The `chunk_size` is the minimum size of the internal buffers. Every read
+    /// uses its own buffer, so it should be about the size of a typical read.
+    pub fn new(chunk_size: BufferAddress) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        ReadbackBelt {
+            chunk_size,
+            free_chunks: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Record a copy of `size` bytes from the `source` buffer at the specified offset
+    /// into `encoder`.
+    ///
+    /// The data can be mapped with [`Readback::map`] once `encoder` is submitted.
+    pub fn read_buffer(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        source: &Buffer,
+        offset: BufferAddress,
+        size: BufferSize,
+        device: &Device,
+    ) -> Readback {
+        let chunk = self.allocate(size.get(), device);
+        encoder.copy_buffer_to_buffer(source, offset, &chunk.buffer, 0, size.get());
+        Readback {
+            chunk: Some(chunk),
+            size: size.get(),
+            bytes_per_row: size.get() as usize,
+            padded_bytes_per_row: size.get() as usize,
+            mapped: false,
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Record a copy of the `source` texture region of `size` into `encoder`.
+    ///
+    /// `bytes_per_row` is the size of one row of texels, or of one row of blocks
+    /// for compressed formats. The rows are padded to [`COPY_BYTES_PER_ROW_ALIGNMENT`](crate::COPY_BYTES_PER_ROW_ALIGNMENT)
+    /// in the readback buffer; use [`ReadbackView::rows`] to skip the padding.
+    ///
+    /// The data can be mapped with [`Readback::map`] once `encoder` is submitted.
+    ///
+    /// # Panics
+    ///
+    /// - `bytes_per_row` is zero.
+    pub fn read_texture(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        source: TextureCopyView,
+        bytes_per_row: u32,
+        size: Extent3d,
+        device: &Device,
+    ) -> Readback {
+        assert_ne!(bytes_per_row, 0, "Texture rows to read must not be empty");
+        let alignment = crate::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (bytes_per_row + alignment - 1) / alignment * alignment;
+        let total_size = padded_bytes_per_row as BufferAddress
+            * size.height as BufferAddress
+            * size.depth as BufferAddress;
+
+        let chunk = self.allocate(total_size, device);
+        encoder.copy_texture_to_buffer(
+            source,
+            BufferCopyView {
+                buffer: &chunk.buffer,
+                layout: TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: padded_bytes_per_row,
+                    rows_per_image: size.height,
+                },
+            },
+            size,
+        );
+        Readback {
+            chunk: Some(chunk),
+            size: total_size,
+            bytes_per_row: bytes_per_row as usize,
+            padded_bytes_per_row: padded_bytes_per_row as usize,
+            mapped: false,
+            sender: self.sender.clone(),
+        }
+    }
+
+    fn allocate(&mut self, size: BufferAddress, device: &Device) -> Chunk {
+        while let Ok(chunk) = self.receiver.try_recv() {
+            self.free_chunks.push(chunk);
+        }
+
+        if let Some(index) = self.free_chunks.iter().position(|chunk| size <= chunk.size) {
+            self.free_chunks.swap_remove(index)
+        } else {
+            let size = self.chunk_size.max(size);
+            #[cfg(not(target_arch = "wasm32"))]
+            wgc::span!(_guard, INFO, "Creating readback chunk of size {}", size);
+            Chunk {
+                buffer: device.create_buffer(&BufferDescriptor {
+                    label: Some("readback"),
+                    size,
+                    usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                size,
+            }
+        }
+    }
+}
+
+/// A read recorded by a [`ReadbackBelt`].
+///
+/// Dropping it without mapping releases its buffer instead of returning it
+/// to the belt, since the copy into it may still be pending.
+pub struct Readback {
+    chunk: Option<Chunk>,
+    size: BufferAddress,
+    bytes_per_row: usize,
+    padded_bytes_per_row: usize,
+    mapped: bool,
+    sender: mpsc::Sender<Chunk>,
+}
+
+impl Readback {
+    /// Map the data for reading.
+    ///
+    /// This has to be called after the encoder the copy was recorded into is submitted!
+    ///
+    /// For the future to complete, `device.poll(...)` must be called elsewhere in the runtime.
+    pub fn map(self) -> impl Future<Output = Result<ReadbackView, BufferAsyncError>> + Send {
+        let map_future = self
+            .chunk
+            .as_ref()
+            .unwrap()
+            .buffer
+            .slice(..self.size)
+            .map_async(MapMode::Read);
+        map_future.map(move |result| {
+            let mut readback = self;
+            result.map(|()| {
+                readback.mapped = true;
+                // Registered for as long as the view lives, like a `BufferView` of the range.
+                readback
+                    .chunk
+                    .as_ref()
+                    .unwrap()
+                    .buffer
+                    .map_context
+                    .lock()
+                    .add(0, None, false)
+                    .expect("Readback buffer has no other view of its mapped range");
+                ReadbackView { readback }
+            })
+        })
+    }
+}
+
+impl Drop for Readback {
+    fn drop(&mut self) {
+        if let Some(chunk) = self.chunk.take() {
+            if self.mapped {
+                chunk.buffer.unmap();
+                // The belt may be gone already, in which case the chunk is simply dropped.
+                let _ = self.sender.send(chunk);
+            }
+        }
+    }
+}
+
+/// Read-only view of the data of a mapped [`Readback`].
+///
+/// The buffer is unmapped and returned to its [`ReadbackBelt`] when the view is dropped.
+pub struct ReadbackView {
+    readback: Readback,
+}
+
+impl ReadbackView {
+    /// Iterates over the rows of a texture read, without their padding.
+    ///
+    /// A buffer read is a single row.
+    pub fn rows(&self) -> impl Iterator<Item = &[u8]> {
+        let bytes_per_row = self.readback.bytes_per_row;
+        self.chunks(self.readback.padded_bytes_per_row)
+            .map(move |row| &row[..bytes_per_row])
+    }
+}
+
+impl Deref for ReadbackView {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        let buffer = &self.readback.chunk.as_ref().unwrap().buffer;
+        Context::buffer_get_mapped_range(&*buffer.context, &buffer.id, 0..self.readback.size)
+    }
+}
+
+impl Drop for ReadbackView {
+    fn drop(&mut self) {
+        // Before the readback unmaps the buffer, which requires its views to be gone.
+        let buffer = &self.readback.chunk.as_ref().unwrap().buffer;
+        buffer.map_context.lock().remove(0, None, false);
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::request_device, CommandEncoderDescriptor, Origin3d, TextureDescriptor,
+        TextureDimension, TextureFormat, TextureUsage,
+    };
+
+    fn read(
+        view_future: impl Future<Output = Result<ReadbackView, BufferAsyncError>>,
+    ) -> ReadbackView {
+        futures::executor::block_on(view_future).unwrap()
+    }
+
+    #[test]
+    fn read_buffer() {
+        let (device, queue) = request_device();
+        let source =
+            device.create_buffer_with_data(&[1, 2, 3, 4, 5, 6, 7, 8], BufferUsage::COPY_SRC);
+        let mut belt = ReadbackBelt::new(64);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let readback = belt.read_buffer(
+            &mut encoder,
+            &source,
+            4,
+            BufferSize::new(4).unwrap(),
+            &device,
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let view = read(readback.map());
+        assert_eq!(&*view, &[5, 6, 7, 8]);
+        assert_eq!(view.rows().collect::<Vec<_>>(), vec![&[5, 6, 7, 8]]);
+    }
+
+    #[test]
+    fn view_keeps_its_range_mapped() {
+        let (device, queue) = request_device();
+        let source = device.create_buffer_with_data(&[1, 2, 3, 4], BufferUsage::COPY_SRC);
+        let mut belt = ReadbackBelt::new(64);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let readback = belt.read_buffer(
+            &mut encoder,
+            &source,
+            0,
+            BufferSize::new(4).unwrap(),
+            &device,
+        );
+        queue.submit(Some(encoder.finish()));
+
+        let view = read(readback.map());
+        let buffer = &view.readback.chunk.as_ref().unwrap().buffer;
+        assert_eq!(
+            buffer.slice(..).try_get_mapped_range_mut().err(),
+            Some(crate::MapError::Overlap(0..4))
+        );
+    }
+
+    #[test]
+    fn read_texture_skips_padding() {
+        let (device, queue) = request_device();
+        let size = Extent3d {
+            width: 3,
+            height: 2,
+            depth: 1,
+        };
+        let texture = device.create_texture_with_data(
+            &queue,
+            &TextureDescriptor {
+                label: None,
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::R8Uint,
+                usage: TextureUsage::COPY_SRC,
+            },
+            &[1, 2, 3, 4, 5, 6],
+        );
+        let mut belt = ReadbackBelt::new(64);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let source = TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+        };
+        let readback = belt.read_texture(&mut encoder, source, 3, size, &device);
+        queue.submit(Some(encoder.finish()));
+
+        let view = read(readback.map());
+        assert_eq!(view.len(), 2 * crate::COPY_BYTES_PER_ROW_ALIGNMENT as usize);
+        assert_eq!(
+            view.rows().collect::<Vec<_>>(),
+            vec![&[1, 2, 3], &[4, 5, 6]]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Texture rows to read must not be empty")]
+    fn read_texture_rejects_empty_rows() {
+        let (device, _queue) = request_device();
+        let texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: 1,
+                height: 1,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::R8Uint,
+            usage: TextureUsage::COPY_SRC,
+        });
+        let mut belt = ReadbackBelt::new(64);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let source = TextureCopyView {
+            texture: &texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+        };
+        belt.read_texture(&mut encoder, source, 0, texture.size, &device);
+    }
+
+    #[test]
+    fn chunks_are_reused() {
+        let (device, queue) = request_device();
+        let source = device.create_buffer_with_data(&[1, 2, 3, 4], BufferUsage::COPY_SRC);
+        let mut belt = ReadbackBelt::new(64);
+        let size = BufferSize::new(4).unwrap();
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let readback = belt.read_buffer(&mut encoder, &source, 0, size, &device);
+        queue.submit(Some(encoder.finish()));
+        drop(read(readback.map()));
+        let resources = device.live_resources();
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let readback = belt.read_buffer(&mut encoder, &source, 0, size, &device);
+        queue.submit(Some(encoder.finish()));
+        assert_eq!(&*read(readback.map()), &[1, 2, 3, 4]);
+        assert_eq!(device.live_resources(), resources);
+    }
+}
@@ -0,0 +1,378 @@
+use super::{texture_format_info, StagingBelt};
+use crate::{
+    BufferAddress, BufferAsyncError, BufferCopyView, BufferDescriptor, BufferUsage, CommandEncoder,
+    CommandEncoderDescriptor, Device, Extent3d, MapMode, Origin3d, Queue, Texture, TextureCopyView,
+    TextureDataLayout, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+};
+use futures::FutureExt;
+use std::{future::Future, ops::Range};
+
+/// Texture uploader is a machine that fills textures with tightly packed texel data.
+///
+/// It stages the data in a [`StagingBelt`], with rows padded to
+/// [`COPY_BYTES_PER_ROW_ALIGNMENT`](crate::COPY_BYTES_PER_ROW_ALIGNMENT), and records
+/// one copy per mip level. A frame goes through `upload`, `finish`, submission and
+/// `recall`, like with the belt itself.
+pub struct TextureUploader {
+    belt: StagingBelt,
+}
+
+impl TextureUploader {
+    /// Create a new texture uploader.
+    ///
+    /// The `chunk_size` is the unit of staging buffer allocation, see [`StagingBelt::new`].
+    pub fn new(chunk_size: BufferAddress, device: &Device) -> Self {
+        TextureUploader {
+            belt: StagingBelt::new(chunk_size, device),
+        }
+    }
+
+    /// Record copies of `data` into all the mip levels and array layers of `texture`.
+    ///
+    /// `texture` needs the `COPY_DST` usage.
+    /// `data` holds the mip levels one after the other, each with its array layers
+    /// (or depth slices) one after the other, and rows tightly packed.
+    ///
+    /// [`TextureUploader::finish`] has to be called before `encoder` is submitted.
+    ///
+    /// # Panics
+    ///
+    /// - The format of `texture` isn't copyable.
+    /// - The size of `data` doesn't match `texture`.
+    pub fn upload(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        texture: &Texture,
+        data: &[u8],
+        device: &Device,
+    ) {
+        let texel_size = copy_texel_size(texture.format);
+        for (mip_level, extent, range) in mip_ranges(texture, texel_size, data.len()) {
+            let bytes_per_row = extent.width * texel_size;
+            self.belt
+                .write_texture_with_encoder(
+                    encoder,
+                    TextureCopyView {
+                        texture,
+                        mip_level,
+                        origin: Origin3d::ZERO,
+                    },
+                    bytes_per_row,
+                    extent,
+                    device,
+                )
+                .copy_from_slice(&data[range]);
+        }
+    }
+
+    /// Close the staging buffers until the GPU is done copying the data from them.
+    ///
+    /// This has to be called before submitting the encoders passed to [`TextureUploader::upload`].
+    pub fn finish(&mut self) {
+        self.belt.finish();
+    }
+
+    /// Recall all of the closed staging buffers back for re-usal.
+    ///
+    /// This has to be called after the encoders closed by `finish` are submitted!
+    pub fn recall(&mut self) -> impl Future<Output = ()> + Send {
+        self.belt.recall()
+    }
+}
+
+impl Device {
+    /// Creates a new [`Texture`] and schedules a write of `data` into all of its mip levels
+    /// and array layers on `queue`.
+    ///
+    /// `data` holds the mip levels one after the other, each with its array layers
+    /// (or depth slices) one after the other, and rows tightly packed.
+    /// `COPY_DST` is added to the usage of `desc`.
+    ///
+    /// # Panics
+    ///
+    /// - The format of `desc` isn't copyable.
+    /// - The size of `data` doesn't match `desc`.
+    pub fn create_texture_with_data(
+        &self,
+        queue: &Queue,
+        desc: &TextureDescriptor,
+        data: &[u8],
+    ) -> Texture {
+        let texel_size = copy_texel_size(desc.format);
+        let texture = self.create_texture(&TextureDescriptor {
+            usage: desc.usage | TextureUsage::COPY_DST,
+            ..*desc
+        });
+        for (mip_level, extent, range) in mip_ranges(&texture, texel_size, data.len()) {
+            queue.write_texture(
+                TextureCopyView {
+                    texture: &texture,
+                    mip_level,
+                    origin: Origin3d::ZERO,
+                },
+                &data[range],
+                TextureDataLayout {
+                    offset: 0,
+                    bytes_per_row: extent.width * texel_size,
+                    rows_per_image: extent.height,
+                },
+                extent,
+            );
+        }
+        texture
+    }
+}
+
+/// Read array layer `layer` of mip level `mip_level` of `texture`, with rows tightly packed.
+///
+/// For 3D textures, `layer` is the depth slice. `texture` needs the `COPY_SRC` usage.
+/// The copy into a readback buffer is submitted to `queue` right away.
+///
+/// For the future to complete, `device.poll(...)` must be called elsewhere in the runtime.
+///
+/// # Panics
+///
+/// - The format of `texture` isn't copyable.
+/// - `mip_level` or `layer` is out of bounds of `texture`.
+pub fn read_texture(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+    mip_level: u32,
+    layer: u32,
+) -> impl Future<Output = Result<Vec<u8>, BufferAsyncError>> + Send {
+    let extent = mip_extent(texture.size, texture.dimension, mip_level);
+    assert!(
+        mip_level < texture.mip_level_count && layer < extent.depth,
+        "Layer {} of mip level {} is out of bounds of a texture of {:?} with {} levels",
+        layer,
+        mip_level,
+        texture.size,
+        texture.mip_level_count
+    );
+    let bytes_per_row = extent.width * copy_texel_size(texture.format);
+    let alignment = crate::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let padded_bytes_per_row = (bytes_per_row + alignment - 1) / alignment * alignment;
+
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("texture readback"),
+        size: padded_bytes_per_row as BufferAddress * extent.height as BufferAddress,
+        usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        TextureCopyView {
+            texture,
+            mip_level,
+            origin: Origin3d {
+                x: 0,
+                y: 0,
+                z: layer,
+            },
+        },
+        BufferCopyView {
+            buffer: &buffer,
+            layout: TextureDataLayout {
+                offset: 0,
+                bytes_per_row: padded_bytes_per_row,
+                rows_per_image: extent.height,
+            },
+        },
+        Extent3d { depth: 1, ..extent },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let map_future = buffer.slice(..).map_async(MapMode::Read);
+    map_future.map(move |result| {
+        result.map(|()| {
+            let view = buffer.slice(..).get_mapped_range();
+            let mut data = Vec::with_capacity(bytes_per_row as usize * extent.height as usize);
+            for row in view.chunks(padded_bytes_per_row as usize) {
+                data.extend_from_slice(&row[..bytes_per_row as usize]);
+            }
+            data
+        })
+    })
+}
+
+/// Extent of mip level `level` of a texture of `size`, with array layers counted in `depth`.
+pub(crate) fn mip_extent(size: Extent3d, dimension: TextureDimension, level: u32) -> Extent3d {
+    Extent3d {
+        width: (size.width >> level).max(1),
+        height: match dimension {
+            TextureDimension::D1 => 1,
+            _ => (size.height >> level).max(1),
+        },
+        depth: match dimension {
+            TextureDimension::D3 => (size.depth >> level).max(1),
+            _ => size.depth,
+        },
+    }
+}
+
+/// Size of a texel of `format`, which must be copyable.
+fn copy_texel_size(format: TextureFormat) -> u32 {
+    let info = texture_format_info(format);
+    assert!(info.copyable, "Texture format {:?} can't be copied", format);
+    info.block_size as u32
+}
+
+/// Splits tightly packed data of `data_size` bytes into the mip levels of `texture`.
+fn mip_ranges(
+    texture: &Texture,
+    texel_size: u32,
+    data_size: usize,
+) -> Vec<(u32, Extent3d, Range<usize>)> {
+    let texel_size = texel_size as usize;
+    let mut offset = 0;
+    let ranges = (0..texture.mip_level_count)
+        .map(|level| {
+            let extent = mip_extent(texture.size, texture.dimension, level);
+            let size =
+                extent.width as usize * extent.height as usize * extent.depth as usize * texel_size;
+            offset += size;
+            (level, extent, offset - size..offset)
+        })
+        .collect();
+    assert_eq!(
+        data_size, offset,
+        "Texture data of {} bytes doesn't match the {} bytes of the texture",
+        data_size, offset
+    );
+    ranges
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use crate::mock::request_device;
+
+    #[test]
+    fn uploader_matches_create_texture_with_data() {
+        use crate::{
+            util::TextureUploader, BufferCopyView, BufferDescriptor, BufferUsage,
+            CommandEncoderDescriptor, Extent3d, MapMode, Origin3d, TextureCopyView,
+            TextureDataLayout, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage,
+            COPY_BYTES_PER_ROW_ALIGNMENT,
+        };
+
+        let (device, queue) = request_device();
+        let desc = TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: 2,
+                height: 2,
+                depth: 1,
+            },
+            mip_level_count: 2,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rg8Uint,
+            usage: TextureUsage::COPY_SRC | TextureUsage::COPY_DST,
+        };
+        let data = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let created = device.create_texture_with_data(&queue, &desc, &data);
+        let uploaded = device.create_texture(&desc);
+        let mut uploader = TextureUploader::new(1024, &device);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        uploader.upload(&mut encoder, &uploaded, &data, &device);
+        uploader.finish();
+
+        let bytes_per_row = COPY_BYTES_PER_ROW_ALIGNMENT;
+        let buffers = [&created, &uploaded]
+            .iter()
+            .map(|texture| {
+                let buffer = device.create_buffer(&BufferDescriptor {
+                    label: None,
+                    size: 3 * bytes_per_row as u64,
+                    usage: BufferUsage::COPY_DST | BufferUsage::MAP_READ,
+                    mapped_at_creation: false,
+                });
+                for mip_level in 0..2 {
+                    encoder.copy_texture_to_buffer(
+                        TextureCopyView {
+                            texture,
+                            mip_level,
+                            origin: Origin3d::ZERO,
+                        },
+                        BufferCopyView {
+                            buffer: &buffer,
+                            layout: TextureDataLayout {
+                                offset: 2 * mip_level as u64 * bytes_per_row as u64,
+                                bytes_per_row,
+                                rows_per_image: 0,
+                            },
+                        },
+                        Extent3d {
+                            width: 2 >> mip_level,
+                            height: 2 >> mip_level,
+                            depth: 1,
+                        },
+                    );
+                }
+                buffer
+            })
+            .collect::<Vec<_>>();
+        queue.submit(Some(encoder.finish()));
+
+        for buffer in buffers.iter() {
+            let slice = buffer.slice(..);
+            futures::executor::block_on(slice.map_async(MapMode::Read)).unwrap();
+            let view = slice.get_mapped_range();
+            let row = |index: usize| &view[index * bytes_per_row as usize..][..4];
+            assert_eq!(row(0), &[1, 2, 3, 4]);
+            assert_eq!(row(1), &[5, 6, 7, 8]);
+            assert_eq!(&row(2)[..2], &[9, 10]);
+        }
+    }
+
+    #[test]
+    fn read_texture_layer() {
+        use crate::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage};
+
+        let (device, queue) = request_device();
+        let texture = device.create_texture_with_data(
+            &queue,
+            &TextureDescriptor {
+                label: None,
+                size: Extent3d {
+                    width: 3,
+                    height: 2,
+                    depth: 2,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: TextureDimension::D2,
+                format: TextureFormat::R8Uint,
+                usage: TextureUsage::COPY_SRC,
+            },
+            &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12],
+        );
+        let data =
+            futures::executor::block_on(crate::util::read_texture(&device, &queue, &texture, 0, 1))
+                .unwrap();
+        assert_eq!(data, vec![7, 8, 9, 10, 11, 12]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Texture format Depth24Plus can't be copied")]
+    fn read_texture_rejects_uncopyable_formats() {
+        use crate::{Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsage};
+
+        let (device, queue) = request_device();
+        let texture = device.create_texture(&TextureDescriptor {
+            label: None,
+            size: Extent3d {
+                width: 1,
+                height: 1,
+                depth: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Depth24Plus,
+            usage: TextureUsage::COPY_SRC,
+        });
+        let _ = crate::util::read_texture(&device, &queue, &texture, 0, 0);
+    }
+}
@@ -0,0 +1,295 @@
+use crate::{
+    Buffer, BufferAddress, BufferAsyncError, BufferDescriptor, BufferSlice, BufferUsage,
+    BufferView, BufferViewMut, Device, MapMode, Queue,
+};
+use bytemuck::Pod;
+use std::{
+    future::Future,
+    marker::PhantomData,
+    mem,
+    ops::{Bound, Deref, DerefMut, RangeBounds},
+};
+
+/// A [`Buffer`] holding elements of type `T`.
+///
+/// Offsets and sizes are counted in elements, and mapped views are `&[T]` or `&mut [T]`,
+/// so the element size is never computed by hand.
+///
+/// The size of `T` must be a non-zero multiple of 4 bytes, so that every element range
+/// meets the [`COPY_BUFFER_ALIGNMENT`](crate::COPY_BUFFER_ALIGNMENT) of copies and writes.
+/// Other element types fail to compile. Mapped views panic if the mapped memory is not
+/// aligned for `T`.
+pub struct TypedBuffer<T> {
+    buffer: Buffer,
+    len: usize,
+    _marker: PhantomData<T>,
+}
+
+/// Size of the elements of a [`TypedBuffer`].
+struct ElementSize<T>(PhantomData<T>);
+
+impl<T> ElementSize<T> {
+    /// The size of `T`, failing to compile with an out of bounds index if it is zero or not
+    /// a multiple of 4.
+    const CHECKED: BufferAddress = [mem::size_of::<T>() as BufferAddress]
+        [((mem::size_of::<T>() == 0) | (mem::size_of::<T>() % 4 != 0)) as usize];
+}
+
+impl<T: Pod> TypedBuffer<T> {
+    /// Creates a new buffer with room for `len` elements.
+    pub fn new(device: &Device, label: Option<&str>, len: usize, usage: BufferUsage) -> Self {
+        TypedBuffer {
+            buffer: device.create_buffer(&BufferDescriptor {
+                label,
+                size: len as BufferAddress * ElementSize::<T>::CHECKED,
+                usage,
+                mapped_at_creation: false,
+            }),
+            len,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a new buffer holding the elements of `data`.
+    pub fn create_with_data(
+        device: &Device,
+        label: Option<&str>,
+        data: &[T],
+        usage: BufferUsage,
+    ) -> Self {
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label,
+            size: data.len() as BufferAddress * ElementSize::<T>::CHECKED,
+            usage,
+            mapped_at_creation: true,
+        });
+        // An empty buffer has no mapped range to get.
+        if !data.is_empty() {
+            buffer
+                .slice(..)
+                .get_mapped_range_mut()
+                .copy_from_slice(bytemuck::cast_slice(data));
+        }
+        buffer.unmap();
+        TypedBuffer {
+            buffer,
+            len: data.len(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the underlying untyped buffer, e.g. to bind it.
+    pub fn buffer(&self) -> &Buffer {
+        &self.buffer
+    }
+
+    /// Returns the number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the buffer holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Use only the elements in `bounds` of this buffer for a given operation.
+    ///
+    /// # Panics
+    ///
+    /// - The range is empty or goes past the end of the buffer.
+    pub fn slice<S: RangeBounds<usize>>(&self, bounds: S) -> TypedBufferSlice<T> {
+        let start = match bounds.start_bound() {
+            Bound::Included(&index) => index,
+            Bound::Excluded(&index) => index + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match bounds.end_bound() {
+            Bound::Included(&index) => index + 1,
+            Bound::Excluded(&index) => index,
+            Bound::Unbounded => self.len,
+        };
+        assert!(
+            start < end && end <= self.len,
+            "Element range {}..{} is empty or out of bounds for a buffer of {} elements",
+            start,
+            end,
+            self.len
+        );
+        let element_size = ElementSize::<T>::CHECKED;
+        TypedBufferSlice {
+            slice: self
+                .buffer
+                .slice(start as BufferAddress * element_size..end as BufferAddress * element_size),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Flushes any pending write operations and unmaps the buffer from host memory.
+    pub fn unmap(&self) {
+        self.buffer.unmap();
+    }
+}
+
+/// Slice into a [`TypedBuffer`].
+///
+/// Created by calling [`TypedBuffer::slice`].
+pub struct TypedBufferSlice<'a, T> {
+    slice: BufferSlice<'a>,
+    _marker: PhantomData<T>,
+}
+
+// Not derived, since that would require `T: Copy`.
+impl<T> Clone for TypedBufferSlice<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TypedBufferSlice<'_, T> {}
+
+impl<'a, T: Pod> TypedBufferSlice<'a, T> {
+    /// Returns the underlying untyped slice, e.g. to set it as a vertex buffer.
+    pub fn untyped(&self) -> BufferSlice<'a> {
+        self.slice
+    }
+
+    /// Map the elements. See [`BufferSlice::map_async`].
+    pub fn map_async(
+        &self,
+        mode: MapMode,
+    ) -> impl Future<Output = Result<(), BufferAsyncError>> + Send {
+        self.slice.map_async(mode)
+    }
+
+    /// Synchronously and immediately map the elements for reading.
+    /// See [`BufferSlice::get_mapped_range`].
+    pub fn get_mapped_range(&self) -> TypedBufferView<'a, T> {
+        TypedBufferView {
+            view: self.slice.get_mapped_range(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Synchronously and immediately map the elements for writing.
+    /// See [`BufferSlice::get_mapped_range_mut`].
+    pub fn get_mapped_range_mut(&self) -> TypedBufferViewMut<'a, T> {
+        TypedBufferViewMut {
+            view: self.slice.get_mapped_range_mut(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+/// Read only view into a mapped [`TypedBuffer`].
+pub struct TypedBufferView<'a, T> {
+    view: BufferView<'a>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> Deref for TypedBufferView<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        bytemuck::cast_slice(&self.view)
+    }
+}
+
+/// Write only view into a mapped [`TypedBuffer`].
+pub struct TypedBufferViewMut<'a, T> {
+    view: BufferViewMut<'a>,
+    _marker: PhantomData<T>,
+}
+
+impl<T: Pod> Deref for TypedBufferViewMut<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        bytemuck::cast_slice(&self.view)
+    }
+}
+
+impl<T: Pod> DerefMut for TypedBufferViewMut<'_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        bytemuck::cast_slice_mut(&mut self.view)
+    }
+}
+
+impl Queue {
+    /// Schedule a write of `data` into `buffer` starting at element `index`.
+    ///
+    /// # Panics
+    ///
+    /// - `data` doesn't fit in `buffer` at `index`.
+    pub fn write<T: Pod>(&self, buffer: &TypedBuffer<T>, index: usize, data: &[T]) {
+        assert!(
+            index + data.len() <= buffer.len,
+            "Writing {} elements at index {} overruns a buffer of {} elements",
+            data.len(),
+            index,
+            buffer.len
+        );
+        self.write_buffer(
+            &buffer.buffer,
+            index as BufferAddress * ElementSize::<T>::CHECKED,
+            bytemuck::cast_slice(data),
+        );
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::request_device;
+
+    #[test]
+    fn create_with_data() {
+        let (device, _queue) = request_device();
+        let buffer = TypedBuffer::create_with_data(
+            &device,
+            Some("typed"),
+            &[1u32, 2, 3, 4],
+            BufferUsage::MAP_READ,
+        );
+        assert_eq!(buffer.len(), 4);
+        let slice = buffer.slice(1..3);
+        let copy = slice;
+        futures::executor::block_on(copy.map_async(MapMode::Read)).unwrap();
+        assert_eq!(&*slice.get_mapped_range(), &[2, 3]);
+        assert_eq!(slice.untyped().get_mapped_range().len(), 8);
+    }
+
+    #[test]
+    fn write() {
+        let (device, queue) = request_device();
+        let buffer = TypedBuffer::<[f32; 2]>::new(
+            &device,
+            None,
+            3,
+            BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+        );
+        queue.write(&buffer, 1, &[[1.0, 2.0], [3.0, 4.0]]);
+        let slice = buffer.slice(..);
+        futures::executor::block_on(slice.map_async(MapMode::Read)).unwrap();
+        assert_eq!(
+            &*slice.get_mapped_range(),
+            &[[0.0, 0.0], [1.0, 2.0], [3.0, 4.0]]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "overruns a buffer of 3 elements")]
+    fn write_out_of_bounds() {
+        let (device, queue) = request_device();
+        let buffer = TypedBuffer::<u32>::new(&device, None, 3, BufferUsage::COPY_DST);
+        queue.write(&buffer, 2, &[1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "is empty or out of bounds")]
+    fn slice_out_of_bounds() {
+        let (device, _queue) = request_device();
+        let buffer = TypedBuffer::<u32>::new(&device, None, 3, BufferUsage::COPY_DST);
+        buffer.slice(2..4);
+    }
+}
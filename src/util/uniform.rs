@@ -0,0 +1,240 @@
+use super::align_to;
+use crate::{
+    Buffer, BufferAddress, BufferDescriptor, BufferUsage, Device, DynamicOffset, MapMode, Queue,
+};
+use futures::{future::join_all, FutureExt};
+use std::{future::Future, sync::mpsc};
+
+struct Chunk {
+    buffer: Buffer,
+    /// Mappable buffer written alongside the chunk, to know when the GPU is done with it.
+    fence: Buffer,
+    offset: BufferAddress,
+}
+
+/// Where [`UniformRing::write`] placed the data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UniformAllocation {
+    /// Index of the buffer holding the data, see [`UniformRing::buffer`].
+    pub buffer_index: usize,
+    /// Offset of the data in the buffer, to pass as a dynamic offset to `set_bind_group`.
+    pub offset: DynamicOffset,
+}
+
+/// Uniform ring is a machine that hands out per-frame uniform data.
+///
+/// It writes data into a ring of `UNIFORM | COPY_DST` buffers at offsets aligned to
+/// [`BIND_BUFFER_ALIGNMENT`](crate::BIND_BUFFER_ALIGNMENT), to be used as dynamic offsets
+/// into a bind group per buffer. The buffers are only ever reused, never released,
+/// so their bind groups can be created once.
+///
+/// A frame goes through `write`, `finish`, submission and `recall`, like with
+/// [`StagingBelt`](super::StagingBelt).
+pub struct UniformRing {
+    chunk_size: BufferAddress,
+    chunks: Vec<Chunk>,
+    /// Chunks that are written to in the current frame.
+    active_chunks: Vec<usize>,
+    /// Chunks that are used by submitted frames.
+    closed_chunks: Vec<usize>,
+    /// Chunks that the GPU is done with and are ready to be used.
+    free_chunks: Vec<usize>,
+    sender: mpsc::Sender<usize>,
+    receiver: mpsc::Receiver<usize>,
+}
+
+impl UniformRing {
+    /// Create a new uniform ring.
+    ///
+    /// The `chunk_size` is the size of each buffer in the ring. It's better when it
+    /// holds all the uniform data of a frame.
+    pub fn new(chunk_size: BufferAddress) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        UniformRing {
+            chunk_size,
+            chunks: Vec::new(),
+            active_chunks: Vec::new(),
+            closed_chunks: Vec::new(),
+            free_chunks: Vec::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Returns the buffer at `index`, to create the bind group for it.
+    pub fn buffer(&self, index: usize) -> &Buffer {
+        &self.chunks[index].buffer
+    }
+
+    /// Returns the number of buffers in the ring. It grows when a frame needs more room.
+    pub fn buffer_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Schedule a write of `data` into the ring, returning where it will land.
+    ///
+    /// `data` is padded with zeros to a multiple of [`COPY_BUFFER_ALIGNMENT`](crate::COPY_BUFFER_ALIGNMENT).
+    ///
+    /// # Panics
+    ///
+    /// - The padded `data` is larger than the chunk size.
+    pub fn write(&mut self, queue: &Queue, data: &[u8], device: &Device) -> UniformAllocation {
+        // Buffer writes work on whole words.
+        let size = align_to(data.len() as BufferAddress, crate::COPY_BUFFER_ALIGNMENT);
+        assert!(
+            size <= self.chunk_size,
+            "Uniform data of {} bytes doesn't fit in chunks of {} bytes",
+            size,
+            self.chunk_size
+        );
+
+        let chunk_size = self.chunk_size;
+        let chunks = &self.chunks;
+        let index = if let Some(&index) = self
+            .active_chunks
+            .iter()
+            .find(|&&index| chunks[index].offset + size <= chunk_size)
+        {
+            index
+        } else if let Some(index) = self.free_chunks.pop() {
+            self.active_chunks.push(index);
+            index
+        } else {
+            #[cfg(not(target_arch = "wasm32"))]
+            wgc::span!(
+                _guard,
+                INFO,
+                "Creating uniform chunk of size {}",
+                chunk_size
+            );
+            self.chunks.push(Chunk {
+                buffer: device.create_buffer(&BufferDescriptor {
+                    label: Some("uniform ring"),
+                    size: chunk_size,
+                    usage: BufferUsage::UNIFORM | BufferUsage::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                fence: device.create_buffer(&BufferDescriptor {
+                    label: Some("uniform ring fence"),
+                    size: crate::COPY_BUFFER_ALIGNMENT,
+                    usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                offset: 0,
+            });
+            self.active_chunks.push(self.chunks.len() - 1);
+            self.chunks.len() - 1
+        };
+
+        let chunk = &mut self.chunks[index];
+        let offset = chunk.offset;
+        if size == data.len() as BufferAddress {
+            queue.write_buffer(&chunk.buffer, offset, data);
+        } else {
+            let mut padded = data.to_vec();
+            padded.resize(size as usize, 0);
+            queue.write_buffer(&chunk.buffer, offset, &padded);
+        }
+        chunk.offset = align_to(offset + size, crate::BIND_BUFFER_ALIGNMENT);
+        UniformAllocation {
+            buffer_index: index,
+            offset: offset as DynamicOffset,
+        }
+    }
+
+    /// Close the chunks written to in this frame until the GPU is done with them.
+    ///
+    /// This has to be called before the frame is submitted to `queue`!
+    pub fn finish(&mut self, queue: &Queue) {
+        for index in self.active_chunks.drain(..) {
+            // The fence becomes mappable once the submission carrying this write completes.
+            queue.write_buffer(
+                &self.chunks[index].fence,
+                0,
+                &[0; crate::COPY_BUFFER_ALIGNMENT as usize],
+            );
+            self.closed_chunks.push(index);
+        }
+    }
+
+    /// Recall all of the closed chunks back for re-usal.
+    ///
+    /// This has to be called after the frame closed by `finish` is submitted!
+    pub fn recall(&mut self) -> impl Future<Output = ()> + Send {
+        while let Ok(index) = self.receiver.try_recv() {
+            let chunk = &mut self.chunks[index];
+            chunk.fence.unmap();
+            chunk.offset = 0;
+            self.free_chunks.push(index);
+        }
+
+        let chunks = &self.chunks;
+        let sender_template = &self.sender;
+        join_all(self.closed_chunks.drain(..).map(|index| {
+            let sender = sender_template.clone();
+            chunks[index]
+                .fence
+                .slice(..)
+                .map_async(MapMode::Read)
+                .inspect(move |_| sender.send(index).unwrap())
+        }))
+        .map(|_| ())
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::mock::{request_device, Command};
+
+    #[test]
+    fn write_pads_and_aligns() {
+        let (device, queue) = request_device();
+        let mut ring = UniformRing::new(1024);
+        let first = ring.write(&queue, &[1; 6], &device);
+        let second = ring.write(&queue, &[2; 4], &device);
+        assert_eq!(
+            (first.buffer_index, first.offset, second.buffer_index),
+            (0, 0, 0)
+        );
+        assert_eq!(second.offset as BufferAddress, crate::BIND_BUFFER_ALIGNMENT);
+        let buffer = ring.buffer(0).mock_id();
+        assert_eq!(
+            queue.take_command_log(),
+            vec![
+                Command::WriteBuffer {
+                    buffer,
+                    offset: 0,
+                    size: 8,
+                },
+                Command::WriteBuffer {
+                    buffer,
+                    offset: crate::BIND_BUFFER_ALIGNMENT,
+                    size: 4,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit in chunks of 16 bytes")]
+    fn write_too_large() {
+        let (device, queue) = request_device();
+        let mut ring = UniformRing::new(16);
+        ring.write(&queue, &[0; 17], &device);
+    }
+
+    #[test]
+    fn chunks_are_reused() {
+        let (device, queue) = request_device();
+        let mut ring = UniformRing::new(256);
+        for _ in 0..3 {
+            ring.write(&queue, &[0; 256], &device);
+            ring.finish(&queue);
+            queue.submit(None);
+            futures::executor::block_on(ring.recall());
+        }
+        // The first frame's chunk is only back after the second recall.
+        assert_eq!(ring.buffer_count(), 2);
+    }
+}
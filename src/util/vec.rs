@@ -0,0 +1,281 @@
+use super::align_to;
+use crate::{Buffer, BufferAddress, BufferDescriptor, BufferUsage, CommandEncoder, Device, Queue};
+use bytemuck::Pod;
+use std::{mem, ops::Deref, ops::Range};
+
+/// A growable array of `T` mirrored into a GPU buffer.
+///
+/// Elements are edited on the CPU side, and [`GpuVec::sync`] uploads the elements
+/// that changed since the previous sync. When the elements no longer fit, the buffer
+/// is reallocated with geometric growth and the unchanged contents are copied over
+/// on the GPU.
+pub struct GpuVec<T> {
+    data: Vec<T>,
+    buffer: Option<Buffer>,
+    /// Number of elements the buffer has room for.
+    capacity: usize,
+    usage: BufferUsage,
+    /// Elements changed since the last sync.
+    dirty: Range<usize>,
+}
+
+impl<T: Pod> GpuVec<T> {
+    /// Creates an empty vector. No buffer is allocated until the first [`GpuVec::sync`].
+    ///
+    /// `COPY_SRC` and `COPY_DST` are added to `usage`, for the uploads and reallocations.
+    pub fn new(usage: BufferUsage) -> Self {
+        GpuVec {
+            data: Vec::new(),
+            buffer: None,
+            capacity: 0,
+            usage: usage | BufferUsage::COPY_SRC | BufferUsage::COPY_DST,
+            dirty: 0..0,
+        }
+    }
+
+    /// Creates an empty vector with a buffer that has room for `capacity` elements.
+    pub fn with_capacity(device: &Device, capacity: usize, usage: BufferUsage) -> Self {
+        let mut vec = Self::new(usage);
+        if capacity != 0 {
+            vec.buffer = Some(vec.create_buffer(device, capacity));
+            vec.capacity = capacity;
+        }
+        vec
+    }
+
+    /// Returns the buffer holding the elements as of the last sync, if one was allocated.
+    ///
+    /// The buffer changes when [`GpuVec::sync`] reallocates it.
+    pub fn buffer(&self) -> Option<&Buffer> {
+        self.buffer.as_ref()
+    }
+
+    /// Returns the number of elements the buffer has room for.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Appends an element.
+    pub fn push(&mut self, value: T) {
+        self.data.push(value);
+        self.mark_dirty(self.data.len() - 1..self.data.len());
+    }
+
+    /// Appends all the elements of `values`.
+    pub fn extend_from_slice(&mut self, values: &[T]) {
+        let start = self.data.len();
+        self.data.extend_from_slice(values);
+        self.mark_dirty(start..self.data.len());
+    }
+
+    /// Shortens the vector to `len` elements. The buffer is kept as is.
+    pub fn truncate(&mut self, len: usize) {
+        self.data.truncate(len);
+        self.dirty.end = self.dirty.end.min(len);
+        if self.dirty.start >= self.dirty.end {
+            self.dirty = 0..0;
+        }
+    }
+
+    /// Removes all the elements. The buffer is kept as is.
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Returns the elements for editing. All of them are uploaded by the next sync.
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        self.mark_dirty(0..self.data.len());
+        &mut self.data
+    }
+
+    /// Uploads the changed elements, reallocating the buffer if they don't fit.
+    ///
+    /// Copies between buffers are recorded into `encoder`, which has to be submitted
+    /// before the buffer is used. Returns `true` if the buffer was reallocated, in which
+    /// case bind groups referring to it must be recreated.
+    pub fn sync(&mut self, device: &Device, queue: &Queue, encoder: &mut CommandEncoder) -> bool {
+        let element_size = mem::size_of::<T>() as BufferAddress;
+        // Buffer writes and copies work on whole words.
+        let write_start = align_down(
+            self.dirty.start as BufferAddress * element_size,
+            crate::COPY_BUFFER_ALIGNMENT,
+        );
+
+        let reallocated = self.data.len() > self.capacity;
+        if reallocated {
+            let capacity = self.data.len().max(self.capacity * 2);
+            #[cfg(not(target_arch = "wasm32"))]
+            wgc::span!(_guard, INFO, "Growing GpuVec to {} elements", capacity);
+            let buffer = self.create_buffer(device, capacity);
+            if let Some(old_buffer) = self.buffer.take() {
+                // The elements before `write_start` are unchanged, copy them over.
+                if write_start != 0 {
+                    encoder.copy_buffer_to_buffer(&old_buffer, 0, &buffer, 0, write_start);
+                }
+            }
+            self.buffer = Some(buffer);
+            self.capacity = capacity;
+        }
+
+        if self.dirty.start < self.dirty.end {
+            let bytes = bytemuck::cast_slice::<T, u8>(&self.data);
+            let write_end = align_to(
+                self.dirty.end as BufferAddress * element_size,
+                crate::COPY_BUFFER_ALIGNMENT,
+            );
+            let buffer = self.buffer.as_ref().unwrap();
+            if write_end as usize <= bytes.len() {
+                queue.write_buffer(
+                    buffer,
+                    write_start,
+                    &bytes[write_start as usize..write_end as usize],
+                );
+            } else {
+                let mut padded = bytes[write_start as usize..].to_vec();
+                padded.resize((write_end - write_start) as usize, 0);
+                queue.write_buffer(buffer, write_start, &padded);
+            }
+            self.dirty = 0..0;
+        }
+
+        reallocated
+    }
+
+    fn create_buffer(&self, device: &Device, capacity: usize) -> Buffer {
+        device.create_buffer(&BufferDescriptor {
+            label: Some("GpuVec"),
+            size: align_to(
+                (capacity * mem::size_of::<T>()) as BufferAddress,
+                crate::COPY_BUFFER_ALIGNMENT,
+            ),
+            usage: self.usage,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn mark_dirty(&mut self, range: Range<usize>) {
+        if range.start == range.end {
+            return;
+        }
+        if self.dirty.start < self.dirty.end {
+            self.dirty = self.dirty.start.min(range.start)..self.dirty.end.max(range.end);
+        } else {
+            self.dirty = range;
+        }
+    }
+}
+
+impl<T> Deref for GpuVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.data
+    }
+}
+
+impl<T: Pod> Extend<T> for GpuVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let start = self.data.len();
+        self.data.extend(iter);
+        self.mark_dirty(start..self.data.len());
+    }
+}
+
+fn align_down(offset: BufferAddress, alignment: BufferAddress) -> BufferAddress {
+    offset - offset % alignment
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        mock::{request_device, Command},
+        CommandEncoderDescriptor, MapMode,
+    };
+
+    fn read_buffer(
+        device: &Device,
+        queue: &Queue,
+        buffer: &Buffer,
+        size: BufferAddress,
+    ) -> Vec<u8> {
+        let readback = device.create_buffer(&BufferDescriptor {
+            label: None,
+            size,
+            usage: BufferUsage::MAP_READ | BufferUsage::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(buffer, 0, &readback, 0, size);
+        queue.submit(Some(encoder.finish()));
+        let slice = readback.slice(..);
+        futures::executor::block_on(slice.map_async(MapMode::Read)).unwrap();
+        let data = slice.get_mapped_range().to_vec();
+        data
+    }
+
+    #[test]
+    fn sync_uploads_dirty_range() {
+        let (device, queue) = request_device();
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let mut vec = GpuVec::<u32>::with_capacity(&device, 8, BufferUsage::VERTEX);
+        vec.extend_from_slice(&[1, 2, 3, 4]);
+        assert!(!vec.sync(&device, &queue, &mut encoder));
+        vec.push(5);
+        assert!(!vec.sync(&device, &queue, &mut encoder));
+        // Nothing changed since the last sync.
+        assert!(!vec.sync(&device, &queue, &mut encoder));
+
+        let buffer = vec.buffer().unwrap().mock_id();
+        assert_eq!(
+            queue.take_command_log(),
+            vec![
+                Command::WriteBuffer {
+                    buffer,
+                    offset: 0,
+                    size: 16,
+                },
+                Command::WriteBuffer {
+                    buffer,
+                    offset: 16,
+                    size: 4,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn truncate_then_push() {
+        let (device, queue) = request_device();
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let mut vec = GpuVec::<u32>::with_capacity(&device, 4, BufferUsage::VERTEX);
+        vec.extend_from_slice(&[1, 2, 3, 4]);
+        vec.truncate(2);
+        vec.sync(&device, &queue, &mut encoder);
+        vec.push(9);
+        vec.sync(&device, &queue, &mut encoder);
+        queue.submit(Some(encoder.finish()));
+
+        assert_eq!(&*vec, &[1, 2, 9]);
+        let data = read_buffer(&device, &queue, vec.buffer().unwrap(), 16);
+        assert_eq!(&data[..12], bytemuck::cast_slice::<u32, u8>(&[1, 2, 9]));
+    }
+
+    #[test]
+    fn growth_keeps_contents() {
+        let (device, queue) = request_device();
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+        let mut vec = GpuVec::<u16>::new(BufferUsage::VERTEX);
+        assert!(vec.buffer().is_none());
+        vec.extend_from_slice(&[1, 2, 3]);
+        assert!(vec.sync(&device, &queue, &mut encoder));
+        assert_eq!(vec.capacity(), 3);
+        vec.push(4);
+        assert!(vec.sync(&device, &queue, &mut encoder));
+        assert_eq!(vec.capacity(), 6);
+        queue.submit(Some(encoder.finish()));
+
+        let data = read_buffer(&device, &queue, vec.buffer().unwrap(), 8);
+        assert_eq!(&data[..], bytemuck::cast_slice::<u16, u8>(&[1, 2, 3, 4]));
+    }
+}
@@ -0,0 +1,226 @@
+//! Checks of descriptors that the backends would otherwise panic on.
+//!
+//! These run before the backend is called by the `Device::try_create_*` methods, so that
+//! invalid descriptors are reported as [`CreateError::Validation`] on every backend.
+
+use crate::{
+    BufferDescriptor, BufferUsage, CreateError, SamplerDescriptor, ShaderModuleSource,
+    TextureDescriptor, TextureDimension, COPY_BUFFER_ALIGNMENT,
+};
+
+/// First word of every SPIR-V module.
+const SPIRV_MAGIC_NUMBER: u32 = 0x0723_0203;
+/// Number of words in the header of a SPIR-V module, before its first instruction.
+const SPIRV_HEADER_LEN: usize = 5;
+
+/// Checks the header of SPIR-V modules and that their instructions fill the module exactly.
+///
+/// The instructions themselves are left to the backend.
+pub(crate) fn validate_shader_module(source: &ShaderModuleSource) -> Result<(), CreateError> {
+    let words = match *source {
+        ShaderModuleSource::SpirV(words) => words,
+        ShaderModuleSource::Wgsl(_) => return Ok(()),
+    };
+    if words.len() < SPIRV_HEADER_LEN {
+        return Err(CreateError::Validation(format!(
+            "SPIR-V module has {} words, which is shorter than its header",
+            words.len()
+        )));
+    }
+    if words[0] != SPIRV_MAGIC_NUMBER {
+        return Err(CreateError::Validation(format!(
+            "SPIR-V module starts with {:#010x} instead of the magic number {:#010x}",
+            words[0], SPIRV_MAGIC_NUMBER
+        )));
+    }
+    if words[3] == 0 {
+        return Err(CreateError::Validation(
+            "SPIR-V module has an id bound of 0".to_string(),
+        ));
+    }
+    let mut offset = SPIRV_HEADER_LEN;
+    while offset < words.len() {
+        // The high half of the first word of an instruction is its length in words.
+        let len = (words[offset] >> 16) as usize;
+        if len == 0 || offset + len > words.len() {
+            return Err(CreateError::Validation(format!(
+                "SPIR-V instruction at word {} has a length of {}, which doesn't fit the module",
+                offset, len
+            )));
+        }
+        offset += len;
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_buffer(desc: &BufferDescriptor) -> Result<(), CreateError> {
+    if desc
+        .usage
+        .contains(BufferUsage::MAP_READ | BufferUsage::MAP_WRITE)
+    {
+        return Err(CreateError::Validation(
+            "Buffer usage can't contain both MAP_READ and MAP_WRITE".to_string(),
+        ));
+    }
+    if desc.mapped_at_creation && desc.size % COPY_BUFFER_ALIGNMENT != 0 {
+        return Err(CreateError::Validation(format!(
+            "Buffer mapped at creation has a size of {}, which is not a multiple of {}",
+            desc.size, COPY_BUFFER_ALIGNMENT
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_texture(desc: &TextureDescriptor) -> Result<(), CreateError> {
+    let size = desc.size;
+    if size.width == 0 || size.height == 0 || size.depth == 0 {
+        return Err(CreateError::Validation(format!(
+            "Texture extent {:?} must not be empty",
+            size
+        )));
+    }
+    if desc.dimension == TextureDimension::D1 && size.height != 1 {
+        return Err(CreateError::Validation(format!(
+            "1D texture extent {:?} must have a height of 1",
+            size
+        )));
+    }
+    let largest_side = match desc.dimension {
+        TextureDimension::D1 => size.width,
+        TextureDimension::D2 => size.width.max(size.height),
+        TextureDimension::D3 => size.width.max(size.height).max(size.depth),
+    };
+    let max_mip_level_count = 32 - largest_side.leading_zeros();
+    if desc.mip_level_count == 0 || desc.mip_level_count > max_mip_level_count {
+        return Err(CreateError::Validation(format!(
+            "Texture of extent {:?} can't have {} mip levels, it has between 1 and {}",
+            size, desc.mip_level_count, max_mip_level_count
+        )));
+    }
+    if !desc.sample_count.is_power_of_two() {
+        return Err(CreateError::Validation(format!(
+            "Texture sample count {} is not a power of two",
+            desc.sample_count
+        )));
+    }
+    if desc.sample_count > 1
+        && (desc.dimension != TextureDimension::D2 || desc.mip_level_count != 1)
+    {
+        return Err(CreateError::Validation(
+            "Multisampled textures must be 2D and have a single mip level".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+pub(crate) fn validate_sampler(desc: &SamplerDescriptor) -> Result<(), CreateError> {
+    if !(desc.lod_min_clamp >= 0.0 && desc.lod_min_clamp <= desc.lod_max_clamp) {
+        return Err(CreateError::Validation(format!(
+            "Sampler LOD clamp {}..{} must be a non-negative range",
+            desc.lod_min_clamp, desc.lod_max_clamp
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Extent3d, TextureFormat, TextureUsage};
+
+    fn texture_desc(size: Extent3d, mip_level_count: u32) -> TextureDescriptor<'static> {
+        TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Rgba8Unorm,
+            usage: TextureUsage::SAMPLED,
+        }
+    }
+
+    #[test]
+    fn shader_module() {
+        let check = |words: &[u32]| validate_shader_module(&ShaderModuleSource::SpirV(words));
+        assert!(check(&[]).is_err());
+        assert!(check(&[SPIRV_MAGIC_NUMBER]).is_err());
+        assert!(check(&[0x0203_0723, 0x0001_0000, 0, 1, 0]).is_err());
+        assert!(check(&[SPIRV_MAGIC_NUMBER, 0x0001_0000, 0, 0, 0]).is_err());
+        assert!(check(&[SPIRV_MAGIC_NUMBER, 0x0001_0000, 0, 1, 0]).is_ok());
+        // OpCapability Shader
+        assert!(check(&[SPIRV_MAGIC_NUMBER, 0x0001_0000, 0, 1, 0, 0x0002_0011, 1]).is_ok());
+        assert!(check(&[SPIRV_MAGIC_NUMBER, 0x0001_0000, 0, 1, 0, 0x0002_0011]).is_err());
+        assert!(check(&[SPIRV_MAGIC_NUMBER, 0x0001_0000, 0, 1, 0, 0x0000_0011]).is_err());
+    }
+
+    #[test]
+    fn buffer() {
+        let desc = BufferDescriptor {
+            label: None,
+            size: 6,
+            usage: BufferUsage::MAP_WRITE | BufferUsage::COPY_SRC,
+            mapped_at_creation: false,
+        };
+        assert!(validate_buffer(&desc).is_ok());
+        assert!(validate_buffer(&BufferDescriptor {
+            mapped_at_creation: true,
+            ..desc
+        })
+        .is_err());
+        assert!(validate_buffer(&BufferDescriptor {
+            usage: BufferUsage::MAP_WRITE | BufferUsage::MAP_READ,
+            ..desc
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn texture_mip_levels() {
+        let size = Extent3d {
+            width: 16,
+            height: 5,
+            depth: 1,
+        };
+        assert!(validate_texture(&texture_desc(size, 5)).is_ok());
+        assert!(validate_texture(&texture_desc(size, 6)).is_err());
+        assert!(validate_texture(&texture_desc(size, 0)).is_err());
+        assert!(validate_texture(&texture_desc(Extent3d { width: 0, ..size }, 1)).is_err());
+    }
+
+    #[test]
+    fn texture_samples() {
+        let size = Extent3d {
+            width: 16,
+            height: 16,
+            depth: 1,
+        };
+        let desc = TextureDescriptor {
+            sample_count: 4,
+            ..texture_desc(size, 1)
+        };
+        assert!(validate_texture(&desc).is_ok());
+        assert!(validate_texture(&TextureDescriptor {
+            sample_count: 3,
+            ..desc
+        })
+        .is_err());
+        assert!(validate_texture(&TextureDescriptor {
+            mip_level_count: 2,
+            ..desc
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn sampler() {
+        let desc = SamplerDescriptor::default();
+        assert!(validate_sampler(&desc).is_ok());
+        assert!(validate_sampler(&SamplerDescriptor {
+            lod_min_clamp: 2.0,
+            lod_max_clamp: 1.0,
+            ..Default::default()
+        })
+        .is_err());
+    }
+}